@@ -91,9 +91,61 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("rule_match longer-rule", |b| {
         b.iter(|| rule_match(black_box(long_rule)))
     });
+    let large_in_rule = format!(
+        "(message.message_id in {{{}}})",
+        (1..=10000)
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let large_in_matcher = compile_rule(&large_in_rule).unwrap();
+    let large_in_message = matchingram::models::Message {
+        message_id: 10000,
+        ..Default::default()
+    };
+    assert!(matches!(
+        large_in_matcher.match_message(&large_in_message),
+        Ok(true)
+    ));
+    c.bench_function("matcher_match in-10000-ids", |b| {
+        b.iter(|| large_in_matcher.match_message(black_box(&large_in_message)))
+    });
+    let unordered_rule = r#"(
+        message.text all {"太平洋" "大西洋" "马里亚纳海沟" "麦哲伦海峡" "白令海峡"} and
+        message.text any {"南冰洋" "北冰洋"} and
+        message.animation
+    )"#;
+    let unordered_matcher = compile_rule(unordered_rule).unwrap();
+    let mut optimized_matcher = compile_rule(unordered_rule).unwrap();
+    optimized_matcher.optimize();
+    let message = matchingram::models::Message {
+        text: Some(MESSAGE_TEST.to_owned()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        unordered_matcher.match_message(&message),
+        Ok(false)
+    ));
+    assert!(matches!(
+        optimized_matcher.match_message(&message),
+        Ok(false)
+    ));
+    c.bench_function("matcher_match unordered-rule", |b| {
+        b.iter(|| unordered_matcher.match_message(black_box(&message)))
+    });
+    c.bench_function("matcher_match optimized-rule", |b| {
+        b.iter(|| optimized_matcher.match_message(black_box(&message)))
+    });
     c.bench_function("compile_rule 1mb-rule", |b| {
         b.iter(|| compile_rule(black_box(size_1mb_rule)))
     });
+    #[cfg(feature = "binary")]
+    {
+        let bytes = compile_rule(size_1mb_rule).unwrap().to_bytes().unwrap();
+        c.bench_function("from_bytes 1mb-rule", |b| {
+            b.iter(|| matchingram::Matcher::from_bytes(black_box(&bytes)))
+        });
+    }
     c.bench_function("rule_match worst-1mb-rule", |b| {
         b.iter(|| rule_match(black_box(size_1mb_rule)))
     });
@@ -0,0 +1,111 @@
+//! 带缓存的匹配器。
+//!
+//! 适用于同一条消息被同一个匹配器反复匹配的场景（例如回放或测试），需要启用 `cache` 功能。
+
+use lru::LruCache;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write};
+use std::hash::Hasher;
+
+use super::matches::Matcher;
+use super::models::Message;
+use super::result::Result;
+
+/// 带缓存的匹配器。
+///
+/// 以消息内容的哈希为键缓存匹配结果，内容相同的消息将直接返回缓存的结果。
+/// 缓存仅在消息不可变时才是安全的，修改已匹配过的消息可能得到过期的结果。
+/// 匹配选项设置了 `url_resolver` 或 `message_history` 时，结果还依赖外部状态，此时不使用缓存。
+///
+/// # 例子
+/// ```
+/// use matchingram::cache::CachedMatcher;
+/// use matchingram::compile_rule;
+/// use matchingram::models::Message;
+///
+/// let matcher = compile_rule(r#"(message.text any {"博彩"})"#)?;
+/// let mut cached_matcher = CachedMatcher::new(matcher, 100);
+/// let message = Message {
+///     text: Some(String::from("东南亚博彩招聘")),
+///     ..Default::default()
+/// };
+///
+/// assert!(cached_matcher.match_message(&message)?);
+/// assert!(cached_matcher.match_message(&message)?);
+/// assert_eq!(1, cached_matcher.hits());
+/// # Ok::<(), matchingram::Error>(())
+/// ```
+pub struct CachedMatcher {
+    /// 匹配器。
+    pub matcher: Matcher,
+    cache: LruCache<u64, bool>,
+    hits: usize,
+}
+
+impl CachedMatcher {
+    /// 创建带缓存的匹配器，`capacity` 为最多缓存的结果数量。
+    pub fn new(matcher: Matcher, capacity: usize) -> Self {
+        CachedMatcher {
+            matcher,
+            cache: LruCache::new(capacity),
+            hits: 0,
+        }
+    }
+
+    /// 匹配消息，优先返回缓存的结果。匹配出错时不缓存。
+    pub fn match_message(&mut self, message: &Message) -> Result<bool> {
+        if !self.is_cacheable() {
+            return self.matcher.match_message(message);
+        }
+
+        let key = message_hash(message);
+        if let Some(&r) = self.cache.get(&key) {
+            self.hits += 1;
+
+            return Ok(r);
+        }
+
+        let r = self.matcher.match_message(message)?;
+        self.cache.put(key, r);
+
+        Ok(r)
+    }
+
+    /// 命中缓存的次数。
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// 清空缓存。
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    // 匹配结果是否只取决于消息内容。链接解析器和消息历史是外部状态，结果可能随时变化。
+    fn is_cacheable(&self) -> bool {
+        let options = &self.matcher.options;
+
+        options.url_resolver.is_none() && options.message_history.is_none()
+    }
+}
+
+// 将格式化的内容直接写入哈希器，避免分配字符串。
+struct HashWriter<'a>(&'a mut DefaultHasher);
+
+impl Write for HashWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+// 计算消息内容的哈希。
+// 消息包含浮点数字段，无法实现 `Hash`，因此以消息的调试表示作为哈希的内容。
+fn message_hash(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    // 写入哈希器不会失败。
+    let _ = write!(HashWriter(&mut hasher), "{:?}", message);
+
+    hasher.finish()
+}
@@ -3,22 +3,27 @@
 use super::lexer::Token;
 use super::matches::{Field, Value};
 use super::operator::Operator;
+use super::ruleset::LineError;
+use derivative::Derivative;
 use thiserror::Error;
 
 /// 错误类别。
-#[derive(Debug, Error)]
+///
+/// 比较 JSON 错误时，仅比较其类别。
+#[derive(Debug, Error, Derivative)]
+#[derivative(PartialEq)]
 pub enum Error {
     /// 应该在这里结束。
-    #[error("it should end here (--> {column:?})")]
-    ShouldEndHere { column: usize },
+    #[error("it should end here (--> line {line}, column {column})")]
+    ShouldEndHere { line: usize, column: usize },
 
     /// 应该是开启的小括号。
-    #[error("it should be `(` (--> {column:?})")]
-    ShouldOpenParenthesisHere { column: usize },
+    #[error("it should be `(` (--> line {line}, column {column})")]
+    ShouldOpenParenthesisHere { line: usize, column: usize },
 
     /// 应该是关闭的小括号。
-    #[error("it should be `)` (--> {column:?})")]
-    ShouldCloseParenthesisHere { column: usize },
+    #[error("it should be `)` (--> line {line}, column {column})")]
+    ShouldCloseParenthesisHere { line: usize, column: usize },
 
     /// 缺失 token 位置信息。
     #[error("there may be a bug: missing token location data (index: {index:?})")]
@@ -28,9 +33,18 @@ pub enum Error {
     #[error("the field `{}` does not support the `{}` operator", field.to_string(), operator.to_string())]
     UnsupportedOperator { field: Field, operator: Operator },
 
-    /// 字段未被启用。
-    #[error("the field `{}` is not officially enabled", field.to_string())]
-    FieldNotEndabled { field: Field },
+    /// 不支持的运算符，并提示字段支持的运算符。
+    #[error(
+        "the field `{}` does not support the `{}` operator, supported operators: {}",
+        field.to_string(),
+        operator.to_string(),
+        describe_operators(supported)
+    )]
+    UnsupportedFieldOperatorSuggestion {
+        field: Field,
+        operator: Operator,
+        supported: Vec<Operator>,
+    },
 
     /// 未知的字段。
     #[error("unknown `{field:?}` field")]
@@ -45,12 +59,12 @@ pub enum Error {
     InvalidValue { value: String, field: String },
 
     /// 缺失字段。
-    #[error("missing field from column {column:?}")]
-    MissingField { column: usize },
+    #[error("missing field from line {line}, column {column}")]
+    MissingField { line: usize, column: usize },
 
     /// 解析中缺失操作符。
-    #[error("missing operator from column {column:?}")]
-    MissingOperator { column: usize },
+    #[error("missing operator from line {line}, column {column}")]
+    MissingOperator { line: usize, column: usize },
 
     /// 字段需要运算符。
     #[error("field `{}` requires operator", field.to_string())]
@@ -61,38 +75,38 @@ pub enum Error {
     FieldRequireValue { field: Field },
 
     /// 缺失值。
-    #[error("missing value from column {column:?}")]
-    MissingValue { column: usize },
+    #[error("missing value from line {line}, column {column}")]
+    MissingValue { line: usize, column: usize },
 
-    #[error("missing quote from column {column:?}")]
-    MissingQuote { column: usize },
+    #[error("missing quote from line {line}, column {column}")]
+    MissingQuote { line: usize, column: usize },
 
     /// 应该是引号。
-    #[error("should be `\"` from column: {column:?}")]
-    ShouldQuoteHere { column: usize },
+    #[error("should be `\"` from line {line}, column {column}")]
+    ShouldQuoteHere { line: usize, column: usize },
 
     /// 应该是关闭的大括号。
-    #[error("should be `}}` from column: {column:?}")]
-    ShouldCloseBraceHere { column: usize },
+    #[error("should be `}}` from line {line}, column {column}")]
+    ShouldCloseBraceHere { line: usize, column: usize },
 
     /// 应该是值。
-    #[error("should be values from column: {column:?}")]
-    ShouldValueHere { column: usize },
+    #[error("should be values from line {line}, column {column}")]
+    ShouldValueHere { line: usize, column: usize },
 
     /// 应该是打开的大括号或引号。
-    #[error("should be `{{` or `\"` from column: {column:?}")]
-    ShouldOpenBraceOrQuote { column: usize },
+    #[error("should be `{{` or `\"` from line {line}, column {column}")]
+    ShouldOpenBraceOrQuote { line: usize, column: usize },
 
     /// 缺失条件。
-    #[error("missing condition from column {column:?}")]
-    MissingCondition { column: usize },
+    #[error("missing condition from line {line}, column {column}")]
+    MissingCondition { line: usize, column: usize },
 
     /// 位置推断失败。
-    #[error("failed to infer position from token `{token:?}`")]
+    #[error("failed to infer position from token `{token}`")]
     InferPositionFailed { token: Token },
 
     /// 缺失 token 位置信息。
-    #[error("token `{token:?}` is missing position information, the {position:?}th")]
+    #[error("token `{token}` is missing position information, the {position:?}th")]
     MissingTokenPosition { position: usize, token: Token },
 
     /// 缺失数据。
@@ -100,16 +114,28 @@ pub enum Error {
     MissingTokenData { index: usize },
 
     /// 整数转换出错。
-    #[error("error in conversion of integer numbers starting in column {column:?}")]
-    IntegerParseFailed { column: usize },
+    #[error("error in conversion of integer numbers starting at line {line}, column {column}")]
+    IntegerParseFailed { line: usize, column: usize },
 
     /// 小数转换出错。
-    #[error("error in conversion of decimal numbers starting in column {column:?}")]
-    DecimalParseFailed { column: usize },
+    #[error("error in conversion of decimal numbers starting at line {line}, column {column}")]
+    DecimalParseFailed { line: usize, column: usize },
+
+    /// 数字分组分隔符的位置不合法。
+    #[error("invalid digit separator at line {line}, column {column}")]
+    InvalidDigitSeparator { line: usize, column: usize },
 
     /// 解析失败。
-    #[error("failed to parse from column {column:?}")]
-    ParseFailed { column: usize },
+    #[error("failed to parse from line {line}, column {column}")]
+    ParseFailed { line: usize, column: usize },
+
+    /// 未知的方言。
+    #[error("unknown dialect `{name}` at line {line}, column {column}")]
+    UnknownDialect {
+        name: String,
+        line: usize,
+        column: usize,
+    },
 
     #[error("the value `{}` is not a string", value.to_string())]
     NotAString { value: Value },
@@ -123,14 +149,170 @@ pub enum Error {
     #[error("cannot reference value in empty list")]
     RefValueInEmptyList,
 
+    /// 运算符只接受单个值。
+    #[error("the `{}` operator of field `{}` requires a single value, got {count}", operator.to_string(), field.to_string())]
+    SingleValueRequired {
+        field: Field,
+        operator: Operator,
+        count: usize,
+    },
+
+    /// 区间需要两个值。
+    #[error("a range requires exactly two values (lower and upper bound), got {count}")]
+    RangeRequiresTwoValues { count: usize },
+
+    /// 值的长度小于限制。
+    #[error("the value `{needle}` of field `{}` is shorter than {min} characters", field.to_string())]
+    NeedleTooShort {
+        field: Field,
+        needle: String,
+        min: usize,
+    },
+
+    /// 条件组的位置超出范围。
+    #[error("the group index {index} is out of range, the matcher has {len} groups")]
+    GroupIndexOutOfRange { index: usize, len: usize },
+
+    /// 模式过于复杂。
+    #[error("the pattern `{pattern}` is too complex: {reason}")]
+    PatternTooComplex {
+        pattern: String,
+        reason: &'static str,
+    },
+
+    /// 展开嵌套的条件组后得到的条件组过多。
+    #[error("too many groups after expanding nested groups: {count} (max {max})")]
+    TooManyExpandedGroups { count: usize, max: usize },
+
+    /// 模式不合法。
+    #[error("invalid pattern `{pattern}` at index {index}: {reason}")]
+    InvalidPattern {
+        index: usize,
+        pattern: String,
+        reason: String,
+    },
+
+    /// 缺失标签和规则的分隔符。
+    #[error("missing delimiter `{delimiter}` between label and rule")]
+    MissingRuleDelimiter { delimiter: String },
+
+    /// 规则的优先级不是整数。
+    #[error("invalid rule priority `{priority}`")]
+    InvalidRulePriority { priority: String },
+
+    /// 规则集加载失败。
+    #[error(
+        "failed to load rule set: {}",
+        errors.iter().map(|e| format!("line {}: {}", e.line, e.error)).collect::<Vec<_>>().join("; ")
+    )]
+    RuleSetLoadFailed { errors: Vec<LineError> },
+
     #[error("{}", source.to_string())]
     #[cfg(feature = "json")]
     Json {
         #[from]
+        #[derivative(PartialEq(compare_with = "json_error_eq"))]
         source: serde_json::Error,
     },
 
+    #[error("{}", source.to_string())]
+    #[cfg(feature = "binary")]
+    Bincode {
+        #[from]
+        #[derivative(PartialEq(compare_with = "bincode_error_eq"))]
+        source: bincode::Error,
+    },
+
     /// 否定结果的托管，用于提前返回。一般会作为错误消息显示，也不表示错误。
     #[error("falsey result returned early, showing this message may be a bug")]
     FalsyValueHosting,
 }
+
+impl Error {
+    /// 错误在规则中的位置，即行和列（均从 1 开始）。
+    pub fn location(&self) -> Option<(usize, usize)> {
+        use Error::*;
+
+        match self {
+            ShouldEndHere { line, column }
+            | ShouldOpenParenthesisHere { line, column }
+            | ShouldCloseParenthesisHere { line, column }
+            | MissingField { line, column }
+            | MissingOperator { line, column }
+            | MissingValue { line, column }
+            | MissingQuote { line, column }
+            | ShouldQuoteHere { line, column }
+            | ShouldCloseBraceHere { line, column }
+            | ShouldValueHere { line, column }
+            | ShouldOpenBraceOrQuote { line, column }
+            | MissingCondition { line, column }
+            | IntegerParseFailed { line, column }
+            | DecimalParseFailed { line, column }
+            | InvalidDigitSeparator { line, column }
+            | UnknownDialect { line, column, .. }
+            | ParseFailed { line, column } => Some((*line, *column)),
+            _ => None,
+        }
+    }
+
+    /// 生成包含出错的规则行以及指向出错列的 `^` 的错误描述。
+    ///
+    /// 没有位置的错误仅包含错误消息。
+    ///
+    /// # 例子
+    /// ```
+    /// use matchingram::compile_rule;
+    ///
+    /// let rule = r#"(message.text eq)"#;
+    /// let err = compile_rule(rule).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     r#"error: should be values from line 1, column 17
+    ///  --> 1:17
+    ///   |
+    /// 1 | (message.text eq)
+    ///   |                 ^"#,
+    ///     err.pretty(rule)
+    /// );
+    /// ```
+    pub fn pretty(&self, rule: &str) -> String {
+        let mut output = format!("error: {}", self);
+
+        let (line, column) = match self.location() {
+            Some(location) => location,
+            None => return output,
+        };
+        let source = rule.lines().nth(line - 1).unwrap_or_default();
+        let gutter = " ".repeat(line.to_string().len());
+        let indent = " ".repeat(column.saturating_sub(1));
+
+        output.push_str(&format!(
+            "\n{gutter}--> {line}:{column}\n{gutter} |\n{line} | {source}\n{gutter} | {indent}^"
+        ));
+
+        output
+    }
+}
+
+// 运算符列表的描述。不支持任何运算符的字段（只能单独作为条件）描述为 `none`。
+fn describe_operators(operators: &[Operator]) -> String {
+    if operators.is_empty() {
+        return String::from("none");
+    }
+
+    operators
+        .iter()
+        .map(|o| o.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(feature = "json")]
+fn json_error_eq(a: &serde_json::Error, b: &serde_json::Error) -> bool {
+    a.classify() == b.classify()
+}
+
+#[cfg(feature = "binary")]
+fn bincode_error_eq(a: &bincode::Error, b: &bincode::Error) -> bool {
+    a.to_string() == b.to_string()
+}
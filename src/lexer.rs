@@ -54,9 +54,54 @@
 //! # Ok::<(), matchingram::Error>(())
 //! ```
 
+use std::fmt;
+
 use super::error::Error;
 use super::result::Result;
 
+/// 字段引用的前缀。
+pub const FIELD_REF_PREFIX: &str = "field:";
+
+/// 选择方言的指令，只能出现在规则的开头。例如 `#lang v1`。
+pub const DIALECT_DIRECTIVE: &str = "#lang";
+
+/// 规则的方言。
+///
+/// 没有方言指令的规则使用最新的方言。
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Dialect {
+    /// 最初的语法，列表中的值只能以空白分隔，不支持注释。文字值中的反斜杠总是保持原样。
+    V1,
+    /// 列表中的值可以使用逗号分隔，支持以 `#` 开头的行尾注释。文字值中的 `\"` 和 `\\` 分别表示 `"` 和 `\`。
+    #[default]
+    V2,
+}
+
+impl Dialect {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "v1" => Some(Dialect::V1),
+            "v2" => Some(Dialect::V2),
+            _ => None,
+        }
+    }
+
+    // 是否允许以逗号分隔列表中的值。
+    fn allows_list_comma(&self) -> bool {
+        *self != Dialect::V1
+    }
+
+    // 是否允许注释。
+    fn allows_comment(&self) -> bool {
+        *self != Dialect::V1
+    }
+
+    /// 是否处理文字值中的转义字符。
+    pub fn allows_escape(&self) -> bool {
+        *self != Dialect::V1
+    }
+}
+
 /// 所有的 Token。
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Token {
@@ -80,6 +125,10 @@ pub enum Token {
     Integer,
     /// 小数。
     Decimal,
+    /// 字段引用。
+    FieldRef, // field:string
+    /// 整数范围。
+    Range, // integer..integer
     /// and 关键字。
     And, // and
     /// or 关键字。
@@ -90,6 +139,31 @@ pub enum Token {
     EOF,
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Token::OpenParenthesis => "(",
+            Token::CloseParenthesis => ")",
+            Token::Field => "field",
+            Token::Operator => "operator",
+            Token::OpenBrace => "{",
+            Token::CloseBrace => "}",
+            Token::Quote => "\"",
+            Token::Letter => "letter",
+            Token::Integer => "integer",
+            Token::Decimal => "decimal",
+            Token::FieldRef => "field reference",
+            Token::Range => "range",
+            Token::And => "and",
+            Token::Or => "or",
+            Token::Not => "not",
+            Token::EOF => "end of input",
+        };
+
+        f.write_str(name)
+    }
+}
+
 type Input = [char];
 
 /// 词法分析器。
@@ -107,12 +181,22 @@ pub struct Lexer<'a> {
     positions: Vec<Position>,
     // 是否处在引号内部。
     is_inside_quotes: bool,
+    // 是否处在大括号内部。
+    is_inside_braces: bool,
+    // 每一行的起始位置。
+    line_starts: Vec<usize>,
+    // 方言。
+    dialect: Dialect,
 }
 
 #[derive(Debug)]
 pub struct Position {
     pub begin: usize,
     pub end: usize,
+    /// 所在行（从 1 开始）。
+    pub line: usize,
+    /// 在所在行中的起始位置。
+    pub column: usize,
 }
 
 impl<'a> Lexer<'a> {
@@ -125,9 +209,25 @@ impl<'a> Lexer<'a> {
             tokens: vec![],
             positions: vec![],
             is_inside_quotes: false,
+            is_inside_braces: false,
+            line_starts: std::iter::once(0)
+                .chain(
+                    input
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, c)| **c == '\n')
+                        .map(|(i, _)| i + 1),
+                )
+                .collect(),
+            dialect: Dialect::default(),
         }
     }
 
+    /// 获取方言。在 [`tokenize`](#method.tokenize) 之后才能确定。
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
     /// 获取输出（token 序列）。
     pub fn output(&self) -> &Vec<Token> {
         &self.tokens
@@ -140,28 +240,26 @@ impl<'a> Lexer<'a> {
 
     /// 将输入转换为 token 序列。
     pub fn tokenize(&mut self) -> Result<()> {
+        self.scan_directive()?;
         while self.cc.is_some() {
-            self.skip_white_space();
+            self.skip_blank();
             if let Some(cc) = self.cc {
                 match cc {
                     '(' => {
                         self.push_token(Token::OpenParenthesis)?;
-                        self.scan();
-                        self.skip_white_space();
-                        if !self.scan_field()? {
-                            return Err(Error::MissingField {
-                                column: self.pos + 1,
-                            });
-                        }
-                        self.scan();
-                        self.skip_white_space();
-                        if !self.scan_operator()? {
-                            self.back();
-                        }
+                        self.scan_cont_head()?;
                     }
                     ')' => self.push_token(Token::CloseParenthesis)?,
-                    '{' => self.push_token(Token::OpenBrace)?,
-                    '}' => self.push_token(Token::CloseBrace)?,
+                    '{' => {
+                        self.is_inside_braces = true;
+                        self.push_token(Token::OpenBrace)?;
+                    }
+                    '}' => {
+                        self.is_inside_braces = false;
+                        self.push_token(Token::CloseBrace)?;
+                    }
+                    // 大括号内的逗号和空白一样作为值之间的分隔符。
+                    ',' if self.is_list_comma(self.pos) => (),
                     '"' => {
                         self.is_inside_quotes = !self.is_inside_quotes;
                         self.push_token(Token::Quote)?;
@@ -169,15 +267,20 @@ impl<'a> Lexer<'a> {
                             self.scan();
                             if !self.scan_letter()? {
                                 return Err(Error::MissingQuote {
-                                    column: self.pos + 1,
+                                    line: self.line_of(self.pos),
+                                    column: self.column_of(self.pos) + 1,
                                 });
                             }
                         }
                     }
                     _ => {
-                        if !self.scan_keywords()? && !self.scan_number()? {
+                        if !self.scan_keywords()?
+                            && !self.scan_number()?
+                            && !self.scan_field_ref()?
+                        {
                             return Err(Error::ParseFailed {
-                                column: self.pos + 1,
+                                line: self.line_of(self.pos),
+                                column: self.column_of(self.pos) + 1,
                             });
                         }
                     }
@@ -198,25 +301,22 @@ impl<'a> Lexer<'a> {
             match cc {
                 'a' => {
                     if self.tokenize_and()? {
-                        self.scan();
-                        self.skip_white_space();
-                        if !self.scan_field()? {
-                            return Err(Error::MissingField {
-                                column: self.pos + 1,
-                            });
-                        }
-                        self.scan();
-                        self.skip_white_space();
-                        if !self.scan_operator()? {
-                            self.back();
-                        }
+                        self.scan_cont_head()?;
+
+                        Ok(true)
+                    } else {
+                        Ok(false)
+                    }
+                }
+                'o' => {
+                    if self.tokenize_or()? {
+                        self.scan_cont_head()?;
 
                         Ok(true)
                     } else {
                         Ok(false)
                     }
                 }
-                'o' => self.tokenize_or(),
                 'n' => self.tokenize_not(),
                 _ => Ok(false),
             }
@@ -225,37 +325,72 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    // 扫描 `(`、`and` 或 `or` 之后的条件开头，即可能取反的字段和运算符。
+    // 条件是嵌套的条件组时只扫描 `not`，`(` 交由下一轮处理。
+    fn scan_cont_head(&mut self) -> Result<()> {
+        self.scan();
+        self.skip_blank();
+        if self.cc == Some(&'n') && self.tokenize_not()? {
+            self.scan();
+            self.skip_blank();
+        }
+        if self.cc == Some(&'(') {
+            self.back();
+            return Ok(());
+        }
+        if !self.scan_field()? {
+            return Err(Error::MissingField {
+                line: self.line_of(self.pos),
+                column: self.column_of(self.pos) + 1,
+            });
+        }
+        self.scan();
+        self.skip_blank();
+        if !self.scan_operator()? {
+            self.back();
+        }
+
+        Ok(())
+    }
+
     // 扫描数字。
     // 包括整数、小数。
     fn scan_number(&mut self) -> Result<bool> {
         let begin_pos = self.pos;
         let signed = self.at_char(begin_pos) == Some(&'-');
-        let mut end_pos = if signed { begin_pos + 1 } else { begin_pos };
-
-        while self.at_char(end_pos).is_integer() {
-            end_pos += 1;
-        }
+        let digits_begin_pos = if signed { begin_pos + 1 } else { begin_pos };
+        let end_pos = self.scan_digits(digits_begin_pos)?;
 
         let end_char = self.at_char(end_pos);
-        let is_integer = end_pos > if signed {begin_pos + 1} else {begin_pos}
-            // 检查是否合法结束
-            && (end_char.is_white_space() || match end_char {
-                Some(&'}') => true,
-                Some(&')') => true,
-                _ => false,
-            });
+        let is_integer = end_pos > digits_begin_pos && self.is_number_end(end_pos);
 
         if is_integer {
             self.scan_at(end_pos - 1);
-            self.push_token_position(
-                Token::Integer,
-                Position {
-                    begin: begin_pos,
-                    end: end_pos,
-                },
-            );
+            self.push_token_position(Token::Integer, self.position(begin_pos, end_pos));
 
             Ok(true)
+        } else if end_pos > digits_begin_pos
+            && end_char == Some(&'.')
+            && self.at_char(end_pos + 1) == Some(&'.')
+        {
+            // 可能是范围，例如 `1..100`。
+            let range_begin_pos = end_pos + 2;
+            let signed = self.at_char(range_begin_pos) == Some(&'-');
+            let digits_begin_pos = if signed {
+                range_begin_pos + 1
+            } else {
+                range_begin_pos
+            };
+            let range_end_pos = self.scan_digits(digits_begin_pos)?;
+
+            if range_end_pos > digits_begin_pos && self.is_number_end(range_end_pos) {
+                self.scan_at(range_end_pos - 1);
+                self.push_token_position(Token::Range, self.position(begin_pos, range_end_pos));
+
+                Ok(true)
+            } else {
+                Ok(false)
+            }
         } else if end_char == Some(&'.') {
             // 可能是小数
             let mut con_pos = end_pos + 1; // 继续扫描的位置
@@ -264,24 +399,11 @@ impl<'a> Lexer<'a> {
                 con_pos += 1;
             }
 
-            let end_char = self.at_char(con_pos);
-            let is_decimal = con_pos > end_pos + 1
-            // 检查是否合法结束
-            && (end_char.is_white_space() || match end_char {
-                Some(&'}') => true,
-                Some(&')') => true,
-                _ => false,
-            });
+            let is_decimal = con_pos > end_pos + 1 && self.is_number_end(con_pos);
 
             if is_decimal {
                 self.scan_at(con_pos - 1);
-                self.push_token_position(
-                    Token::Decimal,
-                    Position {
-                        begin: begin_pos,
-                        end: con_pos,
-                    },
-                );
+                self.push_token_position(Token::Decimal, self.position(begin_pos, con_pos));
 
                 Ok(true)
             } else {
@@ -292,6 +414,119 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    // 扫描整数部分的数字，返回结束位置。
+    // 整数部分允许使用 `_` 或 `,` 作为数字分组分隔符。大括号内的逗号是值之间的分隔符，不能用于数字分组。
+    fn scan_digits(&self, begin_pos: usize) -> Result<usize> {
+        let mut end_pos = begin_pos;
+        while self.at_char(end_pos).is_integer()
+            || (end_pos > begin_pos
+                && self.at_char(end_pos).is_some_and(is_digit_separator)
+                && !self.is_list_comma(end_pos))
+        {
+            end_pos += 1;
+        }
+        self.check_digit_separators(begin_pos, end_pos)?;
+
+        Ok(end_pos)
+    }
+
+    // 指定位置是否是数字合法的结束位置。
+    fn is_number_end(&self, pos: usize) -> bool {
+        let end_char = self.at_char(pos);
+
+        end_char.is_white_space()
+            || self.is_list_comma(pos)
+            || matches!(end_char, Some(&'}') | Some(&')'))
+    }
+
+    // 指定位置是否是大括号内作为值分隔符的逗号。
+    fn is_list_comma(&self, pos: usize) -> bool {
+        self.dialect.allows_list_comma() && self.is_inside_braces && self.at_char(pos) == Some(&',')
+    }
+
+    // 扫描开头的方言指令，指令独占一行。
+    fn scan_directive(&mut self) -> Result<()> {
+        self.skip_white_space();
+        let directive = DIALECT_DIRECTIVE.chars().collect::<Vec<_>>();
+        let mut begin_pos = self.pos + directive.len();
+        // 指令后必须是空白或输入的结尾，否则只是以 `#lang` 开头的普通注释（例如 `#language`）。
+        if !self.input[self.pos..].starts_with(&directive)
+            || (self.at_char(begin_pos).is_some() && !self.at_char(begin_pos).is_white_space())
+        {
+            return Ok(());
+        }
+
+        while self.at_char(begin_pos) != Some(&'\n') && self.at_char(begin_pos).is_white_space() {
+            begin_pos += 1;
+        }
+        let mut end_pos = begin_pos;
+        while self.at_char(end_pos).is_some() && self.at_char(end_pos) != Some(&'\n') {
+            end_pos += 1;
+        }
+        let name = self.input[begin_pos..end_pos]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_owned();
+
+        self.dialect = Dialect::from_name(&name).ok_or_else(|| Error::UnknownDialect {
+            name,
+            line: self.line_of(begin_pos),
+            column: self.column_of(begin_pos) + 1,
+        })?;
+        self.scan_at(end_pos);
+
+        Ok(())
+    }
+
+    // 检查数字分组分隔符的位置，不能位于末尾或连续出现。
+    fn check_digit_separators(&self, begin_pos: usize, end_pos: usize) -> Result<()> {
+        for pos in begin_pos..end_pos {
+            if self.at_char(pos).is_some_and(is_digit_separator)
+                && (pos + 1 == end_pos || self.at_char(pos + 1).is_some_and(is_digit_separator))
+            {
+                return Err(Error::InvalidDigitSeparator {
+                    line: self.line_of(pos),
+                    column: self.column_of(pos) + 1,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // 扫描字段引用。
+    // 以 `field:` 为前缀，后接字段名称。
+    fn scan_field_ref(&mut self) -> Result<bool> {
+        let begin_pos = self.pos;
+        for (i, c) in FIELD_REF_PREFIX.chars().enumerate() {
+            if self.at_char(begin_pos + i) != Some(&c) {
+                return Ok(false);
+            }
+        }
+
+        let mut end_pos = begin_pos + FIELD_REF_PREFIX.len();
+        let mut end_char = self.at_char(end_pos);
+        while end_char.is_some()
+            && !end_char.is_white_space()
+            && !self.is_list_comma(end_pos)
+            && end_char != Some(&'}')
+            && end_char != Some(&')')
+        {
+            end_pos += 1;
+            end_char = self.at_char(end_pos);
+        }
+
+        if end_pos > begin_pos + FIELD_REF_PREFIX.len() {
+            self.scan_at(end_pos - 1);
+            self.push_token_position(Token::FieldRef, self.position(begin_pos, end_pos));
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     // 扫描字面值（字符串）
     fn scan_letter(&mut self) -> Result<bool> {
         // 如果不在引号内部，则不扫描。
@@ -304,6 +539,13 @@ impl<'a> Lexer<'a> {
         let mut separator = self.at_char(end_pos);
         // 如果没有被双引号或换行截断，继续扫描
         while separator.is_some() && separator != Some(&'"') && separator != Some(&'\n') {
+            // 跳过转义的双引号和反斜杠，它们不会截断文字。
+            if separator == Some(&'\\')
+                && self.dialect.allows_escape()
+                && matches!(self.at_char(end_pos + 1), Some(&'"') | Some(&'\\'))
+            {
+                end_pos += 1;
+            }
             end_pos += 1;
             separator = self.at_char(end_pos);
         }
@@ -313,13 +555,7 @@ impl<'a> Lexer<'a> {
 
         if is_letter {
             self.scan_at(end_pos - 1);
-            self.push_token_position(
-                Token::Letter,
-                Position {
-                    begin: begin_pos,
-                    end: end_pos,
-                },
-            );
+            self.push_token_position(Token::Letter, self.position(begin_pos, end_pos));
 
             Ok(true)
         } else {
@@ -332,42 +568,15 @@ impl<'a> Lexer<'a> {
         use Token::*;
 
         let position = match &token {
-            OpenParenthesis => Position {
-                begin: self.pos,
-                end: self.pos + 1,
-            },
-            CloseParenthesis => Position {
-                begin: self.pos,
-                end: self.pos + 1,
-            },
-            OpenBrace => Position {
-                begin: self.pos,
-                end: self.pos + 1,
-            },
-            CloseBrace => Position {
-                begin: self.pos,
-                end: self.pos + 1,
-            },
-            Quote => Position {
-                begin: self.pos,
-                end: self.pos + 1,
-            },
-            And => Position {
-                begin: self.pos - 2,
-                end: self.pos + 1,
-            },
-            Or => Position {
-                begin: self.pos - 1,
-                end: self.pos + 1,
-            },
-            Not => Position {
-                begin: self.pos - 2,
-                end: self.pos + 1,
-            },
-            EOF => Position {
-                begin: self.pos,
-                end: self.pos,
-            },
+            OpenParenthesis => self.position(self.pos, self.pos + 1),
+            CloseParenthesis => self.position(self.pos, self.pos + 1),
+            OpenBrace => self.position(self.pos, self.pos + 1),
+            CloseBrace => self.position(self.pos, self.pos + 1),
+            Quote => self.position(self.pos, self.pos + 1),
+            And => self.position(self.pos - 2, self.pos + 1),
+            Or => self.position(self.pos - 1, self.pos + 1),
+            Not => self.position(self.pos - 2, self.pos + 1),
+            EOF => self.position(self.pos, self.pos),
             _ => return Err(Error::InferPositionFailed { token: token }),
         };
         self.push_token_position(token, position);
@@ -375,6 +584,26 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    // 创建位置，并计算所在的行和列。
+    fn position(&self, begin: usize, end: usize) -> Position {
+        Position {
+            begin,
+            end,
+            line: self.line_of(begin),
+            column: self.column_of(begin),
+        }
+    }
+
+    // 指定位置所在的行（从 1 开始）。
+    fn line_of(&self, pos: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= pos)
+    }
+
+    // 指定位置在所在行中的偏移。
+    fn column_of(&self, pos: usize) -> usize {
+        pos - self.line_starts[self.line_of(pos) - 1]
+    }
+
     fn push_token_position(&mut self, token: Token, position: Position) {
         self.positions.push(position);
         self.tokens.push(token);
@@ -395,15 +624,12 @@ impl<'a> Lexer<'a> {
     }
 
     fn scan_field(&mut self) -> Result<bool> {
-        if self.cc == Some(&'n') && self.tokenize_not()? {
-            self.scan();
-            self.skip_white_space();
-        }
         let begin_pos = self.pos;
         let mut cur_pos = begin_pos;
         let mut end_char = self.at_char(cur_pos);
 
-        while end_char != Some(&')') && !end_char.is_white_space() {
+        // 以右括号、空白或输入结尾截断。
+        while end_char.is_some() && end_char != Some(&')') && !end_char.is_white_space() {
             cur_pos += 1;
             end_char = self.at_char(cur_pos);
         }
@@ -411,13 +637,7 @@ impl<'a> Lexer<'a> {
         let is_field = cur_pos > begin_pos;
 
         if is_field {
-            self.push_token_position(
-                Token::Field,
-                Position {
-                    begin: begin_pos,
-                    end: cur_pos,
-                },
-            );
+            self.push_token_position(Token::Field, self.position(begin_pos, cur_pos));
             self.scan_at(cur_pos - 1);
         }
 
@@ -432,8 +652,12 @@ impl<'a> Lexer<'a> {
         if self.cc == Some(&'a') && self.is_and_keywords() {
             return Ok(false);
         }
+        if self.cc == Some(&'o') && self.is_or_keywords() {
+            return Ok(false);
+        }
 
-        while end_char != Some(&')') && !end_char.is_white_space() {
+        // 以右括号、空白或输入结尾截断。
+        while end_char.is_some() && end_char != Some(&')') && !end_char.is_white_space() {
             cur_pos += 1;
             end_char = self.at_char(cur_pos);
         }
@@ -441,13 +665,7 @@ impl<'a> Lexer<'a> {
         let is_operator = cur_pos > begin_pos;
 
         if is_operator {
-            self.push_token_position(
-                Token::Operator,
-                Position {
-                    begin: begin_pos,
-                    end: cur_pos,
-                },
-            );
+            self.push_token_position(Token::Operator, self.position(begin_pos, cur_pos));
             self.scan_at(cur_pos - 1);
         }
 
@@ -455,7 +673,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn tokenize_or(&mut self) -> Result<bool> {
-        if self.at_char(self.pos + 1) == Some(&'r') && self.at_char(self.pos + 2).is_white_space() {
+        if self.is_or_keywords() {
             self.scan_at(self.pos + 1);
             self.push_token(Token::Or)?;
 
@@ -483,6 +701,11 @@ impl<'a> Lexer<'a> {
             && self.at_char(self.pos + 3).is_white_space()
     }
 
+    // 当前位置是否是 `or` 关键字。
+    fn is_or_keywords(&self) -> bool {
+        self.at_char(self.pos + 1) == Some(&'r') && self.at_char(self.pos + 2).is_white_space()
+    }
+
     // 扫描下一个字符并自增指针位置。
     fn scan(&mut self) -> Option<&char> {
         self.pos += 1;
@@ -523,6 +746,20 @@ impl<'a> Lexer<'a> {
         return self.pos - begin_pos;
     }
 
+    // 跳过空白字符和注释。
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_white_space();
+            if self.cc == Some(&'#') && self.dialect.allows_comment() {
+                while self.cc.is_some() && self.cc != Some(&'\n') {
+                    self.scan();
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
     /// 分析是否结束。
     pub fn is_end(&self) -> bool {
         self.pos > self.input.len() - 1
@@ -609,3 +846,8 @@ impl IsInteger for Option<&char> {
         }
     }
 }
+
+/// 是否是数字分组分隔符。
+pub fn is_digit_separator(c: &char) -> bool {
+    c == &'_' || c == &','
+}
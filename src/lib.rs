@@ -20,6 +20,8 @@
 
 #![feature(min_specialization)]
 
+#[cfg(feature = "cache")]
+pub mod cache;
 pub mod error;
 pub mod falsey;
 pub mod lexer;
@@ -27,7 +29,11 @@ pub mod matches;
 pub mod models;
 pub mod operator;
 pub mod parser;
+pub mod pattern;
+pub mod prelude;
 pub mod result;
+pub mod ruleset;
+pub mod text;
 pub mod truthy;
 
 #[doc(inline)]
@@ -59,21 +65,21 @@ use result::Result;
 /// # Ok::<(), matchingram::Error>(())
 /// ```
 pub fn rule_match<S: Into<String>>(rule: S, message: &Message) -> Result<bool> {
-    let mut matcher = compile_rule(rule)?;
+    let matcher = compile_rule(rule)?;
 
-    matcher_match(&mut matcher, message)
+    matcher_match(&matcher, message)
 }
 
 /// 使用匹配器对象匹配消息。
 ///
 /// 通过 [`compile_rule`](fn.compile_rule.html) 函数编译规则得到匹配器。
-pub fn matcher_match(matcher: &mut Matcher, message: &Message) -> Result<bool> {
+pub fn matcher_match(matcher: &Matcher, message: &Message) -> Result<bool> {
     matcher.match_message(message)
 }
 
 /// 使用匹配器对象匹配消息的 JSON 数据。
 #[cfg(feature = "json")]
-pub fn matcher_match_json<S: Into<String>>(matcher: &mut Matcher, json_data: S) -> Result<bool> {
+pub fn matcher_match_json<S: Into<String>>(matcher: &Matcher, json_data: S) -> Result<bool> {
     let message: Message = serde_json::from_str(&json_data.into())?;
 
     matcher.match_message(&message)
@@ -95,9 +101,9 @@ pub fn matcher_match_json<S: Into<String>>(matcher: &mut Matcher, json_data: S)
 /// ```
 #[cfg(feature = "json")]
 pub fn rule_match_json<S1: Into<String>, S2: Into<String>>(rule: S1, json: S2) -> Result<bool> {
-    let mut matcher = compile_rule(rule)?;
+    let matcher = compile_rule(rule)?;
 
-    matcher_match_json(&mut matcher, json)
+    matcher_match_json(&matcher, json)
 }
 
 /// 将字符串表达式规则编译为匹配器对象。
@@ -106,3 +112,12 @@ pub fn rule_match_json<S1: Into<String>, S2: Into<String>>(rule: S1, json: S2) -
 pub fn compile_rule<S: Into<String>>(rule: S) -> Result<Matcher> {
     Matcher::from_rule(rule)
 }
+
+/// 将字符串表达式规则编译为匹配器对象，失败时返回可读的错误描述。
+///
+/// 错误描述由 [`Error::pretty`](enum.Error.html#method.pretty) 生成，包含出错的规则行和位置指示。
+pub fn compile_rule_verbose<S: Into<String>>(rule: S) -> std::result::Result<Matcher, String> {
+    let rule = rule.into();
+
+    Matcher::from_rule(rule.as_str()).map_err(|e| e.pretty(&rule))
+}
@@ -1,89 +1,27 @@
 //! 消息匹配实现。
 
-use lazy_static::lazy_static;
-use maplit::hashmap;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
 use std::str::FromStr;
-use strum_macros::{EnumString, ToString};
+use std::sync::Arc;
+use strum_macros::{EnumIter, EnumString, ToString};
 
 use super::error::Error;
 use super::falsey::UnwrapOrFalseyHosting;
-use super::models::Message;
-use super::operator::{prelude::*, Operator};
+use super::models::{Message, User};
+use super::operator::{in_::ValueSet, prelude::*, Operator};
+use super::pattern::Patterns;
 use super::result::Result;
+use super::text;
 use super::truthy::IsTruthy;
 
 pub type ContGroups = Vec<Vec<Cont>>;
 pub type Values = Vec<Value>;
 
-lazy_static! {
-    static ref FIELD_OPERATORS: HashMap<&'static Field, &'static [Operator]> = {
-        use Field::*;
-        use Operator::*;
-
-        hashmap! {
-            &MessageFromId                  => &[Eq, Gt, Ge, Le][..],
-            &MessageFromIsBot               => &[][..],
-            &MessageFromFirstName           => &[Eq, In, Any, All, Hd][..],
-            &MessageFromLastName            => &[Eq, In, Any, All, Hd][..],
-            &MessageFromFullName            => &[Eq, In, Any, All, Hd][..],
-            &MessageFromLanguageCode        => &[Eq, In, Hd][..],
-            &MessageForwardFromChat         => &[][..],
-            &MessageForwardFromChatId       => &[Eq, Gt, Ge, Le][..],
-            &MessageForwardFromChatType     => &[Eq, In][..],
-            &MessageForwardFromChatTitle    => &[Eq, Any, All, Hd][..],
-            &MessageReplyToMessage          => &[][..],
-            &MessageText                    => &[Eq, In, Any, All][..],
-            &MessageTextLen                 => &[Eq, Gt, Ge, Le][..],
-            &MessageAnimation               => &[][..],
-            &MessageAnimationDuration       => &[Eq, Gt, Ge, Le][..],
-            &MessageAnimationFileName       => &[Eq, Any, All, Hd][..],
-            &MessageAnimationMimeType       => &[Eq, In, Hd][..],
-            &MessageAnimationFileSize       => &[Eq, Gt, Ge, Le][..],
-            &MessageAudio                   => &[][..],
-            &MessageAudioDuration           => &[Eq, Gt, Ge, Le][..],
-            &MessageAudioPerformer          => &[Eq, All, Any, Hd][..],
-            &MessageAudioMimeType           => &[Eq, In, Hd][..],
-            &MessageAudioFileSize           => &[Eq, Gt, Ge, Le][..],
-            &MessageDocument                => &[][..],
-            &MessageDocumentFileName        => &[Eq, All, Any, Hd][..],
-            &MessageDocumentMimeType        => &[Eq, In, Hd][..],
-            &MessageDocumentFileSize        => &[Eq, Gt, Ge, Le][..],
-            &MessagePhoto                   => &[][..],
-            &MessageSticker                 => &[][..],
-            &MessageStickerIsAnimated       => &[][..],
-            &MessageStickerEmoji            => &[Eq, In][..],
-            &MessageStickerSetName          => &[Eq, All, Any, Hd][..],
-            &MessageVideo                   => &[][..],
-            &MessageVideoDuration           => &[Eq, Gt, Ge, Le][..],
-            &MessageVideoMimeType           => &[Eq, In, Hd][..],
-            &MessageVideoFileSize           => &[Eq, Gt, Ge, Le][..],
-            &MessageVoice                   => &[][..],
-            &MessageVoiceDuration           => &[Eq, Gt, Ge, Le][..],
-            &MessageVoiceMimeType           => &[Eq, In, Hd][..],
-            &MessageVoiceFileSize           => &[Eq, Gt, Ge, Le][..],
-            &MessageCaption                 => &[Eq, All, Any, Hd][..],
-            &MessageCaptionLen              => &[Eq, Gt, Ge, Le][..],
-            &MessageDice                    => &[][..],
-            &MessageDiceEmoji               => &[Eq, In][..],
-            &MessagePoll                    => &[][..],
-            &MessagePollType                => &[Eq, In][..],
-            &MessageVenue                   => &[][..],
-            &MessageVenueTitle              => &[Eq, All, Any, Hd][..],
-            &MessageVenueAddress            => &[Eq, All, Any, Hd][..],
-            &MessageLocation                => &[][..],
-            &MessageLocationLongitude       => &[Eq, Gt, Ge, Le][..],
-            &MessageLocationLatitude        => &[Eq, Gt, Ge, Le][..],
-            &MessageNewChatMembers          => &[][..],
-            &MessageLeftChatMember          => &[][..],
-            &MessageNewChatTitle            => &[][..],
-            &MessageNewChatPhoto            => &[][..],
-            &MessagePinnedMessage           => &[][..],
-            &MessageIsServiceMessage        => &[][..],
-            &MessageIsCommand               => &[][..],
-        }
-    };
-}
+// 骰子消息可能出现的全部 emoji。
+const DICE_EMOJIS: &[&str] = &["🎲", "🎯", "🏀", "⚽", "🎰", "🎳"];
 
 /// 匹配器。一般作为表达式的编译目标。
 ///
@@ -102,12 +40,18 @@ lazy_static! {
 ///             field: Field::MessageText,
 ///             operator: Some(Operator::Any),
 ///             value: Some(vec![Value::from_str("柬埔寨"), Value::from_str("东南亚")]),
+///             is_optional: false,
+///             value_set: None,
+///             patterns: None,
 ///         },
 ///         Cont {
 ///             is_negative: false,
 ///             field: Field::MessageText,
 ///             operator: Some(Operator::Any),
 ///             value: Some(vec![Value::from_str("菠菜"), Value::from_str("博彩")]),
+///             is_optional: false,
+///             value_set: None,
+///             patterns: None,
 ///         },
 ///     ],
 ///     vec![Cont {
@@ -115,9 +59,12 @@ lazy_static! {
 ///         field: Field::MessageText,
 ///         operator: Some(Operator::All),
 ///         value: Some(vec![Value::from_str("承接"), Value::from_str("广告")]),
+///         is_optional: false,
+///         value_set: None,
+///         patterns: None,
 ///     }],
 /// ];
-/// let mut matcher = Matcher::new(groups);
+/// let matcher = Matcher::new(groups);
 /// // 两条典型的东南亚博彩招人消息
 /// let message_text1 = format!("柬埔寨菠菜需要的来");
 /// let message_text2 = format!("东南亚博彩招聘");
@@ -148,11 +95,352 @@ lazy_static! {
 /// ```
 /// **注意**：匹配器中的所有条件之间都没有显式的关系存在，因为匹配器中每一个独立的组之间一定是 `or` 关系，组内的条件之间一定是 `and` 关系。即：已存在隐式的关系表达。
 #[derive(Debug, Default)]
+#[cfg_attr(
+    any(feature = "json", feature = "binary"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Matcher {
     /// 条件组序列。
     pub groups: ContGroups,
-    // 上个组的匹配结果。
-    is_last_match: bool,
+    /// 所有条件组共同的前置条件，它们之间是 `and` 关系。参考 [`factor_common_prefix`](struct.Matcher.html#method.factor_common_prefix)。
+    pub common_prefix: Vec<Cont>,
+    /// 匹配选项。
+    pub options: MatchOptions,
+    /// 条件组的权重序列，与条件组一一对应。用于计算 [`score`](struct.Matcher.html#method.score)。
+    ///
+    /// 当权重都为正数时，匹配成功等价于得分大于 `0`。[`match_message`](struct.Matcher.html#method.match_message) 不考虑权重，
+    /// 权重为 `0` 或负数的组匹配成功时它仍然返回 `true`，得分却不会增加。需要按得分判断时请使用 `score(..)? > 0.0`。
+    pub weights: Vec<f64>,
+}
+
+/// 输出还原的规则表达式。参考 [`to_rule`](struct.Matcher.html#method.to_rule)。
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_rule())
+    }
+}
+
+/// 求值器。持有匹配器的引用。
+///
+/// 匹配过程不会修改匹配器，多个线程可以共享同一个匹配器（例如放在 `Arc` 中）并同时匹配消息。
+///
+/// ```
+/// use matchingram::compile_rule;
+/// use matchingram::matches::Evaluator;
+/// use matchingram::models::Message;
+///
+/// let matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text all {"承接" "广告"})"#)?;
+/// let evaluator = Evaluator::new(&matcher);
+/// let message = Message {
+///     text: Some(String::from("东南亚博彩招聘")),
+///     ..Default::default()
+/// };
+///
+/// assert!(evaluator.match_message(&message)?);
+/// # Ok::<(), matchingram::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Evaluator<'a> {
+    matcher: &'a Matcher,
+}
+
+impl<'a> Evaluator<'a> {
+    /// 创建匹配器的求值器。
+    pub fn new(matcher: &'a Matcher) -> Self {
+        Evaluator { matcher }
+    }
+
+    /// 匹配消息，每次调用都是一次独立的匹配。
+    pub fn match_message(&self, message: &Message) -> Result<bool> {
+        Ok(self.match_message_detailed(message)?.matched)
+    }
+
+    /// 匹配消息并返回详细的匹配结果。参考 [`MatchResult`](struct.MatchResult.html)。
+    pub fn match_message_detailed(&self, message: &Message) -> Result<MatchResult> {
+        let decisive_group = if self
+            .matcher
+            .match_conts(&self.matcher.common_prefix, message)?
+        {
+            self.loop_match(message)?
+        } else {
+            None
+        };
+
+        Ok(MatchResult {
+            matched: decisive_group.is_some(),
+            decisive_group,
+        })
+    }
+
+    // 依次匹配条件组，返回第一个匹配成功的组的位置。
+    fn loop_match(&self, message: &Message) -> Result<Option<usize>> {
+        for (position, conts) in self.matcher.groups.iter().enumerate() {
+            if self.matcher.match_conts(conts, message)? {
+                return Ok(Some(position));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// 详细的匹配结果。参考 [`Matcher::match_message_detailed`](struct.Matcher.html#method.match_message_detailed)。
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MatchResult {
+    /// 是否匹配成功。
+    pub matched: bool,
+    /// 决定匹配成功的条件组（第一个匹配成功的组）的位置。匹配失败时为 `None`。
+    pub decisive_group: Option<usize>,
+}
+
+/// 匹配报告。参考 [`Matcher::match_message_detail`](struct.Matcher.html#method.match_message_detail)。
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MatchReport {
+    /// 匹配成功的条件组（第一个匹配成功的组）的位置。
+    pub group_index: usize,
+    /// 组中成立的条件的位置，包括成立的可选条件。不包括共同前置条件。
+    pub matched: Vec<usize>,
+}
+
+/// 匹配选项。
+///
+/// 所有选项默认关闭，此时匹配行为与规则表达式的字面含义完全一致。
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    any(feature = "json", feature = "binary"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct MatchOptions {
+    /// 要求条件引用的字段全部存在。
+    ///
+    /// 默认情况下字段不存在会被视作条件不成立（取反后则成立）。启用此选项后，组内任意条件引用的字段不存在都将令整个组匹配失败，无论条件是否取反。
+    pub require_all_fields: bool,
+    /// 在 `any`/`all` 比较前从目标内容中删除的标点字符。
+    ///
+    /// 用于应对 `c.a.s.i.n.o` 这类以标点分割关键字的躲避手段，值不受影响。为空表示不删除。
+    pub ignored_punctuation: Vec<char>,
+    /// 在 `eq`/`any`/`all` 比较前将目标内容中连续的空白字符折叠为单个空格。
+    ///
+    /// 首尾的空白同样只会被折叠而不会被删除。如果同时设置了 `ignored_punctuation`，会先删除标点再折叠空白。
+    pub collapse_whitespace: bool,
+    /// 在比较前将目标内容中的同形字符（外形与拉丁字母相同的西里尔字母和希腊字母）替换为拉丁字母。
+    ///
+    /// 用于应对以西里尔字母 `а` 替换拉丁字母 `a` 这类躲避手段，值不受影响。需要启用 `homoglyph` 功能。
+    #[cfg(feature = "homoglyph")]
+    pub fold_homoglyphs: bool,
+    /// 在 `any`/`all` 比较前尝试解码目标内容中的 base64 或百分号编码的片段，并将解码结果追加到目标内容之后。
+    ///
+    /// 用于应对以编码隐藏关键字的躲避手段。这是一种启发式的检测，普通的单词也可能被误识别为 base64 编码，存在误判的风险。
+    /// 参考 [`text::decode_segments`](../text/fn.decode_segments.html)。
+    pub decode_encoded: bool,
+    /// 在 `any`/`all` 比较前将目标内容转换为小写并替换其中的 leetspeak 字符（例如 `c4s1n0`），将结果追加到目标内容之后。
+    ///
+    /// 原始内容仍然参与比较，因此不影响对数字的匹配。由于替换后的内容是小写的，值中的字母也应该使用小写。
+    /// 参考 [`text::fold_leetspeak`](../text/fn.fold_leetspeak.html)。需要启用 `leetspeak` 功能。
+    #[cfg(feature = "leetspeak")]
+    pub fold_leetspeak: bool,
+    /// 在比较前将目标内容中的 `\r\n` 和 `\r` 换行符统一替换为 `\n`。
+    ///
+    /// 用于以 `eq` 比较完整的多行模板，值中的换行应为 `\n`。参考 [`text::normalize_line_endings`](../text/fn.normalize_line_endings.html)。
+    pub normalize_line_endings: bool,
+    /// 在 `any`/`all` 比较前使用调用者提供的解析器展开目标内容中的链接（例如短链接），并将展开结果追加到目标内容之后。
+    ///
+    /// 目标内容中每个包含 `.` 的单词（以空白分割）都会交给解析器，解析器返回 `None` 表示不展开。本库不会发起任何网络请求。
+    #[cfg_attr(any(feature = "json", feature = "binary"), serde(skip))]
+    pub url_resolver: Option<UrlResolver>,
+    /// 在比较 emoji 字段（`message.sticker.emoji` 和 `message.dice.emoji`）前删除其中的变体选择符和肤色修饰符。
+    ///
+    /// 例如 `👍🏽` 将被视作 `👍`，值不受影响。参考 [`text::strip_emoji_modifiers`](../text/fn.strip_emoji_modifiers.html)。
+    pub strip_emoji_modifiers: bool,
+    /// 调用者提供的消息历史，用于 `message.is_repeat` 字段。
+    ///
+    /// 未提供时 `message.is_repeat` 字段不存在。本库不会保存任何消息。
+    #[cfg_attr(any(feature = "json", feature = "binary"), serde(skip))]
+    pub message_history: Option<Arc<dyn MessageHistory>>,
+}
+
+/// 链接解析器。参考 [`MatchOptions::url_resolver`](struct.MatchOptions.html#structfield.url_resolver)。
+///
+/// ```
+/// use matchingram::compile_rule;
+/// use matchingram::matches::UrlResolver;
+/// use matchingram::models::Message;
+///
+/// let mut matcher = compile_rule(r#"(message.text any {"evil.com"})"#)?;
+/// matcher.options.url_resolver = Some(UrlResolver::new(|url| match url {
+///     "bit.ly/x" => Some(String::from("https://evil.com")),
+///     _ => None,
+/// }));
+/// let message = Message {
+///     text: Some(String::from("点击 bit.ly/x 领取")),
+///     ..Default::default()
+/// };
+///
+/// assert!(matcher.match_message(&message)?);
+/// # Ok::<(), matchingram::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct UrlResolver(Arc<ResolveFn>);
+
+// 链接解析函数。
+type ResolveFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+impl UrlResolver {
+    /// 从函数创建链接解析器。
+    pub fn new<F>(resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        UrlResolver(Arc::new(resolver))
+    }
+
+    // 展开文本中的链接。
+    fn expand(&self, text: &str) -> Vec<String> {
+        text.split_whitespace()
+            .filter(|word| word.contains('.'))
+            .filter_map(|word| (self.0)(word))
+            .collect()
+    }
+}
+
+impl fmt::Debug for UrlResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UrlResolver")
+    }
+}
+
+/// 消息历史。参考 [`MatchOptions::message_history`](struct.MatchOptions.html#structfield.message_history)。
+///
+/// 由调用者实现，时间窗口和存储方式也由调用者决定。
+///
+/// ```
+/// use matchingram::compile_rule;
+/// use matchingram::matches::MessageHistory;
+/// use matchingram::models::{Message, User};
+/// use std::sync::Arc;
+///
+/// struct History;
+///
+/// impl MessageHistory for History {
+///     fn is_repeat(&self, sender_id: i64, text: &str) -> bool {
+///         sender_id == 10086 && text == "加群领红包"
+///     }
+/// }
+///
+/// let mut matcher = compile_rule(r#"(message.is_repeat)"#)?;
+/// matcher.options.message_history = Some(Arc::new(History));
+/// let message = Message {
+///     from: Some(User {
+///         id: 10086,
+///         is_bot: false,
+///         first_name: String::from("Spammer"),
+///         last_name: None,
+///         username: None,
+///         language_code: None,
+///     }),
+///     text: Some(String::from("加群领红包")),
+///     ..Default::default()
+/// };
+///
+/// assert!(matcher.match_message(&message)?);
+/// # Ok::<(), matchingram::Error>(())
+/// ```
+pub trait MessageHistory: Send + Sync {
+    /// 发送者是否在时间窗口内发送过相同的文本。
+    fn is_repeat(&self, sender_id: i64, text: &str) -> bool;
+}
+
+impl fmt::Debug for dyn MessageHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MessageHistory")
+    }
+}
+
+impl MatchOptions {
+    // 根据选项规范化消息文本（`message.text` 和 `message.caption`）。未启用任何相关选项时不产生复制。
+    fn normalize<'a>(
+        &self,
+        target: &'a Option<String>,
+        operator: &Operator,
+    ) -> Cow<'a, Option<String>> {
+        let mut target = Cow::Borrowed(target);
+
+        if self.normalize_line_endings {
+            if let Some(data) = target.as_ref() {
+                target = Cow::Owned(Some(text::normalize_line_endings(data)));
+            }
+        }
+
+        if let Some(resolver) = &self.url_resolver {
+            if matches!(operator, Operator::Any | Operator::All | Operator::Contains) {
+                if let Some(data) = target.as_ref() {
+                    let urls = resolver.expand(data);
+                    if !urls.is_empty() {
+                        target = Cow::Owned(Some(format!("{}\n{}", data, urls.join("\n"))));
+                    }
+                }
+            }
+        }
+
+        if self.decode_encoded
+            && matches!(operator, Operator::Any | Operator::All | Operator::Contains)
+        {
+            if let Some(data) = target.as_ref() {
+                let segments = text::decode_segments(data);
+                if !segments.is_empty() {
+                    target = Cow::Owned(Some(format!("{}\n{}", data, segments.join("\n"))));
+                }
+            }
+        }
+
+        #[cfg(feature = "homoglyph")]
+        if self.fold_homoglyphs {
+            if let Some(data) = target.as_ref() {
+                target = Cow::Owned(Some(text::fold_homoglyphs(data)));
+            }
+        }
+
+        #[cfg(feature = "leetspeak")]
+        if self.fold_leetspeak
+            && matches!(operator, Operator::Any | Operator::All | Operator::Contains)
+        {
+            if let Some(data) = target.as_ref() {
+                let folded = text::fold_leetspeak(data).join("\n");
+                target = Cow::Owned(Some(format!("{}\n{}", data, folded)));
+            }
+        }
+
+        if !self.ignored_punctuation.is_empty()
+            && matches!(operator, Operator::Any | Operator::All | Operator::Contains)
+        {
+            if let Some(data) = target.as_ref() {
+                target = Cow::Owned(Some(text::strip_chars(data, &self.ignored_punctuation)));
+            }
+        }
+
+        if self.collapse_whitespace
+            && matches!(
+                operator,
+                Operator::Eq | Operator::Any | Operator::All | Operator::Contains
+            )
+        {
+            if let Some(data) = target.as_ref() {
+                target = Cow::Owned(Some(text::collapse_whitespace(data)));
+            }
+        }
+
+        target
+    }
+
+    // 根据选项删除 emoji 中的修饰符。未启用时不产生复制。
+    fn strip_emoji_modifiers<'a>(&self, target: &'a Option<String>) -> Cow<'a, Option<String>> {
+        match target {
+            Some(data) if self.strip_emoji_modifiers => {
+                Cow::Owned(Some(text::strip_emoji_modifiers(data)))
+            }
+            _ => Cow::Borrowed(target),
+        }
+    }
 }
 
 impl Matcher {
@@ -170,24 +458,344 @@ impl Matcher {
         Ok(matcher)
     }
 
+    /// 将匹配器序列化为紧凑的二进制数据，用于缓存编译结果。需要启用 `binary` 功能。
+    ///
+    /// 选项中的 [`url_resolver`](struct.MatchOptions.html#structfield.url_resolver) 不会被序列化。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::matches::Matcher;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"})"#)?;
+    /// let bytes = matcher.to_bytes()?;
+    ///
+    /// assert_eq!(matcher.groups, Matcher::from_bytes(&bytes)?.groups);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    #[cfg(feature = "binary")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// 从 [`to_bytes`](#method.to_bytes) 产生的二进制数据中加载匹配器，不需要重新解析规则。需要启用 `binary` 功能。
+    ///
+    /// 正则表达式和 `in` 值的集合不会被序列化，加载时重新构建。
+    #[cfg(feature = "binary")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut matcher: Self = bincode::deserialize(bytes)?;
+        matcher.rebuild_skipped()?;
+
+        Ok(matcher)
+    }
+
+    /// 将匹配器序列化为 JSON，字段和运算符使用规则中的名称表示。需要启用 `json` 功能。
+    ///
+    /// 与 [`to_bytes`](#method.to_bytes) 相同，选项中的 `url_resolver`、编译后的正则表达式和 `in` 值的集合不会被序列化。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::matches::Matcher;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"})"#)?;
+    /// let json = matcher.to_json()?;
+    ///
+    /// assert!(json.contains(r#""field":"message.text","operator":"any""#));
+    /// assert_eq!(matcher.groups, Matcher::from_json(&json)?.groups);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// 从 [`to_json`](#method.to_json) 产生的 JSON 中加载匹配器。需要启用 `json` 功能。
+    ///
+    /// 引用了未知字段或运算符的 JSON 会加载失败。正则表达式和 `in` 值的集合在加载时重新构建。
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut matcher: Self = serde_json::from_str(json)?;
+        matcher.rebuild_skipped()?;
+
+        Ok(matcher)
+    }
+
+    // 重建未被序列化的正则表达式和值的集合。
+    #[cfg(any(feature = "json", feature = "binary"))]
+    fn rebuild_skipped(&mut self) -> Result<()> {
+        let conts = self.groups.iter_mut().flatten();
+        for cont in conts.chain(self.common_prefix.iter_mut()) {
+            match cont.operator {
+                Some(Operator::AllMatches) | Some(Operator::AnyMatches) => {
+                    cont.patterns = Some(Patterns::compile(cont.value()?)?);
+                }
+                Some(Operator::In) | Some(Operator::NotIn)
+                    if !cont
+                        .value()?
+                        .iter()
+                        .any(|v| matches!(v, Value::FieldRef(_))) =>
+                {
+                    cont.value_set = Some(build_value_set(&cont.field, cont.value()?)?);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 解析规则表达式创建匹配器对象，规则中的字段可以使用别名。
+    ///
+    /// 别名在解析时被替换为对应的字段，别名优先于同名的字段。
+    ///
+    /// ```
+    /// use matchingram::matches::{Field, Matcher};
+    /// use maplit::hashmap;
+    ///
+    /// let aliases = hashmap! { String::from("text") => Field::MessageText };
+    /// let matcher = Matcher::from_rule_with_aliases(r#"(text any {"x"})"#, aliases)?;
+    ///
+    /// assert_eq!(Field::MessageText, matcher.groups[0][0].field);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn from_rule_with_aliases<S: Into<String>>(
+        rule: S,
+        aliases: HashMap<String, Field>,
+    ) -> Result<Self> {
+        use super::lexer::Lexer;
+        use super::parser::Parser;
+
+        let input = rule.into().chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        let parser = Parser::new(&mut lexer)?.with_aliases(aliases);
+        let matcher = parser.parse()?;
+
+        Ok(matcher)
+    }
+
+    /// 解析规则表达式创建匹配器对象，并检查规则是否超出限制。
+    ///
+    /// ```
+    /// use matchingram::matches::{Matcher, RuleLimits};
+    ///
+    /// let limits = RuleLimits { min_needle_length: 2 };
+    ///
+    /// assert!(Matcher::from_rule_with_limits(r#"(message.text any {"博彩" "a"})"#, &limits).is_err());
+    /// assert!(Matcher::from_rule_with_limits(r#"(message.text any {"博彩" "ad"})"#, &limits).is_ok());
+    /// ```
+    pub fn from_rule_with_limits<S: Into<String>>(rule: S, limits: &RuleLimits) -> Result<Self> {
+        let matcher = Self::from_rule(rule)?;
+        matcher.check_limits(limits)?;
+
+        Ok(matcher)
+    }
+
+    /// 检查匹配器中的条件是否超出限制，返回遇到的第一个错误。
+    pub fn check_limits(&self, limits: &RuleLimits) -> Result<()> {
+        let conts = self
+            .common_prefix
+            .iter()
+            .chain(self.groups.iter().flatten());
+        for cont in conts {
+            if !matches!(
+                cont.operator,
+                Some(Operator::Any | Operator::All | Operator::Contains)
+            ) {
+                continue;
+            }
+
+            for value in cont.value.iter().flatten() {
+                if let Value::Letter(needle) = value {
+                    if needle.chars().count() < limits.min_needle_length {
+                        return Err(Error::NeedleTooShort {
+                            field: cont.field,
+                            needle: needle.clone(),
+                            min: limits.min_needle_length,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// 使用条件组创建匹配器对象。
     pub fn new(groups: ContGroups) -> Self {
         Matcher {
             groups: groups,
-            is_last_match: true,
+            common_prefix: vec![],
+            options: Default::default(),
+            weights: vec![],
+        }
+    }
+
+    /// 从决策表创建匹配器对象。
+    ///
+    /// 表中的每一行对应一个条件，行中的值全部作为文字值。`relation` 决定行与行之间的关系：
+    /// `RowRelation::And` 表示所有行构成同一个条件组，`RowRelation::Or` 表示每一行独立构成一个条件组。
+    ///
+    /// ```
+    /// use matchingram::matches::{Field, Matcher, RowRelation};
+    /// use matchingram::models::Message;
+    /// use matchingram::operator::Operator;
+    ///
+    /// let rows = [
+    ///     (Field::MessageText, Operator::Any, vec![format!("柬埔寨"), format!("东南亚")]),
+    ///     (Field::MessageText, Operator::Any, vec![format!("菠菜"), format!("博彩")]),
+    /// ];
+    /// let matcher = Matcher::from_table(&rows, RowRelation::And)?;
+    /// let message = Message {
+    ///     text: Some(format!("柬埔寨菠菜需要的来")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(matcher.match_message(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn from_table(
+        rows: &[(Field, Operator, Vec<String>)],
+        relation: RowRelation,
+    ) -> Result<Self> {
+        let mut conts = vec![];
+        for (field, operator, values) in rows {
+            let value = values.iter().map(|v| Value::from_str(v)).collect();
+
+            conts.push(Cont::with_operator(false, *field, *operator, value)?);
         }
+
+        let groups = match relation {
+            RowRelation::And => vec![conts],
+            RowRelation::Or => conts.into_iter().map(|cont| vec![cont]).collect(),
+        };
+
+        Ok(Self::new(groups))
+    }
+}
+
+/// 规则的限制。参考 [`Matcher::check_limits`](struct.Matcher.html#method.check_limits)。
+///
+/// 所有限制默认关闭。
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RuleLimits {
+    /// `any`/`all` 运算的值的最小长度（字符数）。过短的值（例如 `"a"`）容易导致误匹配。
+    pub min_needle_length: usize,
+}
+
+/// 匹配过程的记录。参考 [`Matcher::explain`](struct.Matcher.html#method.explain)。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// 匹配结果。
+    pub is_matched: bool,
+    /// 全部条件的匹配记录，共同前置条件在前，其余按组的顺序排列。
+    pub conts: Vec<ContTrace>,
+}
+
+/// 条件的匹配记录。
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContTrace {
+    /// 条件所在的组的位置，共同前置条件为 `None`。
+    pub group: Option<usize>,
+    /// 条件在组中的位置。
+    pub index: usize,
+    /// 字段。
+    pub field: Field,
+    /// 条件是否成立（已取反）。
+    pub is_matched: bool,
+    /// 条件引用的字段是否不存在。
+    ///
+    /// 字段不存在时条件的结果来自托管规则（不取反时不成立，取反时成立），而不是真实的比较结果。
+    pub is_absent: bool,
+    // `any`/`all` 条件中被包含的值的数量。
+    needle_count: Option<usize>,
+}
+
+impl ContTrace {
+    /// `any`/`all` 条件中被目标内容包含的值的数量，不受取反影响。其它条件返回 `None`。
+    ///
+    /// 可用于“五个关键字中出现了三个”这类部分匹配的计分。
+    pub fn count(&self) -> Option<usize> {
+        self.needle_count
+    }
+}
+
+/// 样本的试运行结果。参考 [`Matcher::test_against`](struct.Matcher.html#method.test_against)。
+#[derive(Debug, PartialEq)]
+pub struct TestOutcome {
+    /// 预期的匹配结果。
+    pub expected: bool,
+    /// 实际的匹配结果。
+    pub actual: Result<bool>,
+}
+
+impl TestOutcome {
+    /// 实际的匹配结果是否与预期一致，匹配出错视为不一致。
+    pub fn is_passed(&self) -> bool {
+        self.actual
+            .as_ref()
+            .is_ok_and(|actual| *actual == self.expected)
+    }
+}
+
+/// 相互矛盾的条件。参考 [`Matcher::find_contradictions`](struct.Matcher.html#method.find_contradictions)。
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Contradiction {
+    /// 条件组的位置。
+    pub group: usize,
+    /// 字段。
+    pub field: Field,
+    /// 相互矛盾的两个条件在组中的位置。
+    pub conts: (usize, usize),
+}
+
+// 数值范围的边界，第二个元素表示是否包含边界值。
+type Bound = (f64, bool);
+
+// 条件所限定的数值范围。
+struct NumericRange {
+    lower: Bound,
+    upper: Bound,
+}
+
+impl NumericRange {
+    fn is_disjoint(&self, other: &Self) -> bool {
+        let below = |lower: &Bound, upper: &Bound| {
+            lower.0 > upper.0 || (lower.0 == upper.0 && !(lower.1 && upper.1))
+        };
+
+        below(&self.lower, &other.upper) || below(&other.lower, &self.upper)
     }
 }
 
+/// 决策表中行与行之间的关系。
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RowRelation {
+    /// 所有行构成同一个条件组。
+    And,
+    /// 每一行独立构成一个条件组。
+    Or,
+}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(
+    any(feature = "json", feature = "binary"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Value {
     Letter(String),
     Integer(i64),
     Decimal(f64),
+    /// 字段引用，匹配时解析为被引用字段的值。
+    FieldRef(Field),
+    /// 整数范围，包含两端。只能用于 `in` 和 `not_in` 的值列表。
+    Range(i64, i64),
 }
 
 /// 单个条件。
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    any(feature = "json", feature = "binary"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Cont {
     /// 是否取反。
     pub is_negative: bool,
@@ -197,10 +805,30 @@ pub struct Cont {
     pub operator: Option<Operator>,
     /// 值。
     pub value: Option<Values>,
+    /// 是否可选。
+    ///
+    /// 可选条件不成立时不会导致所在的组匹配失败，但成立时会增加得分：组匹配成功后，每个成立的可选条件额外增加一次该组的权重。
+    /// 例如权重为 `2.0` 的组中有两个可选条件成立，该组为 [`Matcher::score`](struct.Matcher.html#method.score) 贡献 `6.0`。
+    ///
+    /// 全部由可选条件构成的组，至少有一个可选条件成立时才匹配成功。
+    ///
+    /// 规则表达式没有表示可选条件的语法，解析器产生的条件都不是可选的，此标记只能通过 API 设置。
+    /// [`Matcher::to_rule`](struct.Matcher.html#method.to_rule) 输出的规则不会保留此标记。
+    pub is_optional: bool,
+    /// 由值构建的集合，用于加速 `in` 运算。
+    ///
+    /// 通过 [`with_operator`](#method.with_operator) 构建的 `in` 条件会预先准备此集合，否则在匹配时临时构建。
+    #[cfg_attr(any(feature = "json", feature = "binary"), serde(skip))]
+    pub value_set: Option<ValueSet>,
+    /// 由值编译的正则表达式，用于 `all_matches` 和 `any_matches` 运算。
+    ///
+    /// 通过 [`with_operator`](#method.with_operator) 构建的条件会预先编译，否则在匹配时临时编译。
+    #[cfg_attr(any(feature = "json", feature = "binary"), serde(skip))]
+    pub patterns: Option<Patterns>,
 }
 
 /// 条件字段。
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumString, ToString)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumString, EnumIter, ToString)]
 pub enum Field {
     /// 消息来源 ID。
     #[strum(serialize = "message.from.id")]
@@ -208,11 +836,11 @@ pub enum Field {
     /// 消息来源是否为 bot。
     #[strum(serialize = "message.from.is_bot")]
     MessageFromIsBot,
-    /// 消息来源用户的姓。
-    #[strum(serialize = "message.from.first_name")]
-    MessageFromFirstName,
     /// 消息来源用户的名。
     #[strum(serialize = "message.from.first_name")]
+    MessageFromFirstName,
+    /// 消息来源用户的姓。
+    #[strum(serialize = "message.from.last_name")]
     MessageFromLastName,
     /// 消息来源用户的全名。
     #[strum(serialize = "message.from.full_name")]
@@ -241,6 +869,12 @@ pub enum Field {
     /// 消息中包含的文本大小。
     #[strum(serialize = "message.text.len")]
     MessageTextLen,
+    /// 消息文本中同一字符连续重复的最长次数。
+    #[strum(serialize = "message.text.max_run")]
+    MessageTextMaxRun,
+    /// 消息文本中包含不可见字符。
+    #[strum(serialize = "message.text.has_invisible")]
+    MessageTextHasInvisible,
     /// 消息中包含动画。
     #[strum(serialize = "message.animation")]
     MessageAnimation,
@@ -265,6 +899,9 @@ pub enum Field {
     /// 消息中的音频的表演者。
     #[strum(serialize = "message.audio.performer")]
     MessageAudioPerformer,
+    /// 消息中的音频的标题。
+    #[strum(serialize = "message.audio.title")]
+    MessageAudioTitle,
     /// 消息中音频的媒体类型。
     #[strum(serialize = "message.audio.mime_type")]
     MessageAudioMimeType,
@@ -379,6 +1016,235 @@ pub enum Field {
     // 消息是否为命令。
     #[strum(serialize = "message.is_command")]
     MessageIsCommand,
+    // 消息所属的话题 ID。
+    #[strum(serialize = "message.message_thread_id")]
+    MessageMessageThreadId,
+    // 消息是否发送在论坛话题中。
+    #[strum(serialize = "message.is_topic_message")]
+    MessageIsTopicMessage,
+    // 新加入成员的用户名列表。
+    #[strum(serialize = "message.new_chat_members.usernames")]
+    MessageNewChatMembersUsernames,
+    // 消息 ID。
+    #[strum(serialize = "message.message_id")]
+    MessageId,
+    // 消息中的全部文本内容。参考 `Message::all_text`。
+    #[strum(serialize = "message.all_text")]
+    MessageAllText,
+    // 被转发的原始消息的发送时间。
+    #[strum(serialize = "message.forward_date")]
+    MessageForwardDate,
+    // 转发时原始消息已发送的秒数。
+    #[strum(serialize = "message.forward_age")]
+    MessageForwardAge,
+    // 消息所属的会话 ID。
+    #[strum(serialize = "message.chat.id")]
+    MessageChatId,
+    // 被回复的消息的发送者 ID。
+    #[strum(serialize = "message.reply_to_message.from.id")]
+    MessageReplyToMessageFromId,
+    // 被回复的消息的发送者用户名。
+    #[strum(serialize = "message.reply_to_message.from.username")]
+    MessageReplyToMessageFromUsername,
+    // 由文本和实体还原的 Markdown 文本。
+    #[strum(serialize = "message.text.markdown")]
+    MessageTextMarkdown,
+    // 文本中提及的用户名列表。
+    #[strum(serialize = "message.mentions")]
+    MessageMentions,
+    // 文本中是否包含双向控制字符。
+    #[strum(serialize = "message.text.has_bidi_control")]
+    MessageTextHasBidiControl,
+    // 文件名中是否包含双向控制字符。
+    #[strum(serialize = "message.document.file_name.has_bidi_control")]
+    MessageDocumentFileNameHasBidiControl,
+    // 文本的 UTF-16 长度，与 Telegram 实体的偏移量单位一致。
+    #[strum(serialize = "message.text.utf16_len")]
+    MessageTextUtf16Len,
+    // 文本中大写字母所占的百分比。
+    #[strum(serialize = "message.text.upper_ratio")]
+    MessageTextUpperRatio,
+    // 文本中是否包含与货币相邻的金额。
+    #[strum(serialize = "message.text.has_amount")]
+    MessageTextHasAmount,
+    // 文本中与货币相邻的最大金额。
+    #[strum(serialize = "message.text.max_amount")]
+    MessageTextMaxAmount,
+    // 转发来源用户的 ID。
+    #[strum(serialize = "message.forward_from.id")]
+    MessageForwardFromId,
+    // 转发来源用户是否为 bot。
+    #[strum(serialize = "message.forward_from.is_bot")]
+    MessageForwardFromIsBot,
+    // 转发来源用户的名字。
+    #[strum(serialize = "message.forward_from.first_name")]
+    MessageForwardFromFirstName,
+    // 转发来源用户的用户名。
+    #[strum(serialize = "message.forward_from.username")]
+    MessageForwardFromUsername,
+    // 发送内联消息的 bot 的 ID。
+    #[strum(serialize = "message.via_bot.id")]
+    MessageViaBotId,
+    // 发送内联消息的 bot 是否为 bot，总是成立。
+    #[strum(serialize = "message.via_bot.is_bot")]
+    MessageViaBotIsBot,
+    // 发送内联消息的 bot 的名字。
+    #[strum(serialize = "message.via_bot.first_name")]
+    MessageViaBotFirstName,
+    // 发送内联消息的 bot 的用户名。
+    #[strum(serialize = "message.via_bot.username")]
+    MessageViaBotUsername,
+    // 发送者是否在近期发送过相同的文本，需要提供消息历史。
+    #[strum(serialize = "message.is_repeat")]
+    MessageIsRepeat,
+    // 消息文本不存在或为空白。
+    #[strum(serialize = "message.text.is_blank")]
+    MessageTextIsBlank,
+    // 消息来源用户的用户名。
+    #[strum(serialize = "message.from.username")]
+    MessageFromUsername,
+}
+
+impl Field {
+    /// 字段的英文描述，用于 [`Matcher::summary`](struct.Matcher.html#method.summary)。
+    ///
+    /// 带值字段的描述是名词短语，不带值的字段的描述是以 `is` 或 `has` 为谓语的陈述。
+    ///
+    /// ```
+    /// use matchingram::matches::Field;
+    ///
+    /// assert_eq!("text length", Field::MessageTextLen.description());
+    /// assert_eq!("sender is a bot", Field::MessageFromIsBot.description());
+    /// ```
+    pub fn description(&self) -> &'static str {
+        match self {
+            Field::MessageFromId => "sender ID",
+            Field::MessageFromIsBot => "sender is a bot",
+            Field::MessageFromFirstName => "sender first name",
+            Field::MessageFromLastName => "sender last name",
+            Field::MessageFromFullName => "sender full name",
+            Field::MessageFromLanguageCode => "sender language code",
+            Field::MessageFromUsername => "sender username",
+            Field::MessageForwardFromChat => "message is forwarded from a chat",
+            Field::MessageForwardFromChatId => "forwarded chat ID",
+            Field::MessageForwardFromChatType => "forwarded chat type",
+            Field::MessageForwardFromChatTitle => "forwarded chat title",
+            Field::MessageForwardFromId => "forwarded user ID",
+            Field::MessageForwardFromIsBot => "forwarded user is a bot",
+            Field::MessageForwardFromFirstName => "forwarded user first name",
+            Field::MessageForwardFromUsername => "forwarded user username",
+            Field::MessageForwardDate => "forward date",
+            Field::MessageForwardAge => "forwarded message age in seconds",
+            Field::MessageReplyToMessage => "message is a reply",
+            Field::MessageReplyToMessageFromId => "replied message sender ID",
+            Field::MessageReplyToMessageFromUsername => "replied message sender username",
+            Field::MessageViaBotId => "inline bot ID",
+            Field::MessageViaBotIsBot => "inline bot is a bot",
+            Field::MessageViaBotFirstName => "inline bot first name",
+            Field::MessageViaBotUsername => "inline bot username",
+            Field::MessageText => "text",
+            Field::MessageTextLen => "text length",
+            Field::MessageTextUtf16Len => "text UTF-16 length",
+            Field::MessageTextMaxRun => "longest run of a repeated character in text",
+            Field::MessageTextUpperRatio => "uppercase percentage of text",
+            Field::MessageTextMaxAmount => "largest currency amount in text",
+            Field::MessageTextMarkdown => "text as Markdown",
+            Field::MessageTextHasInvisible => "text has invisible characters",
+            Field::MessageTextHasBidiControl => "text has bidirectional control characters",
+            Field::MessageTextHasAmount => "text has a currency amount",
+            Field::MessageTextIsBlank => "text is blank",
+            Field::MessageAllText => "all text",
+            Field::MessageMentions => "mentioned usernames",
+            Field::MessageCaption => "caption",
+            Field::MessageCaptionLen => "caption length",
+            Field::MessageAnimation => "message has an animation",
+            Field::MessageAnimationDuration => "animation duration",
+            Field::MessageAnimationFileName => "animation file name",
+            Field::MessageAnimationMimeType => "animation MIME type",
+            Field::MessageAnimationFileSize => "animation file size",
+            Field::MessageAudio => "message has an audio",
+            Field::MessageAudioDuration => "audio duration",
+            Field::MessageAudioPerformer => "audio performer",
+            Field::MessageAudioTitle => "audio title",
+            Field::MessageAudioMimeType => "audio MIME type",
+            Field::MessageAudioFileSize => "audio file size",
+            Field::MessageDocument => "message has a document",
+            Field::MessageDocumentFileName => "document file name",
+            Field::MessageDocumentFileNameHasBidiControl => {
+                "document file name has bidirectional control characters"
+            }
+            Field::MessageDocumentMimeType => "document MIME type",
+            Field::MessageDocumentFileSize => "document file size",
+            Field::MessagePhoto => "message has a photo",
+            Field::MessageSticker => "message has a sticker",
+            Field::MessageStickerIsAnimated => "sticker is animated",
+            Field::MessageStickerEmoji => "sticker emoji",
+            Field::MessageStickerSetName => "sticker set name",
+            Field::MessageVideo => "message has a video",
+            Field::MessageVideoDuration => "video duration",
+            Field::MessageVideoMimeType => "video MIME type",
+            Field::MessageVideoFileSize => "video file size",
+            Field::MessageVoice => "message has a voice",
+            Field::MessageVoiceDuration => "voice duration",
+            Field::MessageVoiceMimeType => "voice MIME type",
+            Field::MessageVoiceFileSize => "voice file size",
+            Field::MessageDice => "message has a dice",
+            Field::MessageDiceEmoji => "dice emoji",
+            Field::MessagePoll => "message has a poll",
+            Field::MessagePollType => "poll type",
+            Field::MessageVenue => "message has a venue",
+            Field::MessageVenueTitle => "venue title",
+            Field::MessageVenueAddress => "venue address",
+            Field::MessageLocation => "message has a location",
+            Field::MessageLocationLongitude => "location longitude",
+            Field::MessageLocationLatitude => "location latitude",
+            Field::MessageNewChatMembers => "message has new chat members",
+            Field::MessageNewChatMembersUsernames => "new chat member usernames",
+            Field::MessageLeftChatMember => "message has a left chat member",
+            Field::MessageNewChatTitle => "message has a new chat title",
+            Field::MessageNewChatPhoto => "message has a new chat photo",
+            Field::MessagePinnedMessage => "message has a pinned message",
+            Field::MessageIsServiceMessage => "message is a service message",
+            Field::MessageIsCommand => "message is a command",
+            Field::MessageIsTopicMessage => "message is in a forum topic",
+            Field::MessageMessageThreadId => "topic ID",
+            Field::MessageIsRepeat => "message is a recent repeat from the sender",
+            Field::MessageId => "message ID",
+            Field::MessageChatId => "chat ID",
+        }
+    }
+}
+
+impl TryFrom<&str> for Field {
+    type Error = Error;
+
+    fn try_from(field: &str) -> Result<Self> {
+        Field::from_str(field).map_err(|_| Error::UnknownField {
+            field: field.to_owned(),
+        })
+    }
+}
+
+/// 以规则中的名称序列化字段。
+#[cfg(any(feature = "json", feature = "binary"))]
+impl serde::Serialize for Field {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(any(feature = "json", feature = "binary"))]
+impl<'de> serde::Deserialize<'de> for Field {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let field = String::deserialize(deserializer)?;
+
+        Field::try_from(field.as_str()).map_err(serde::de::Error::custom)
+    }
 }
 
 pub trait GetSingleValue {
@@ -389,6 +1255,9 @@ pub trait GetSingleValue {
 pub trait RefAnInteger {
     fn ref_an_integer(&self) -> Result<&i64>;
 }
+pub trait RefADecimal {
+    fn ref_a_decimal(&self) -> Result<&f64>;
+}
 
 impl ToString for Value {
     fn to_string(&self) -> String {
@@ -398,6 +1267,8 @@ impl ToString for Value {
             Letter(v) => v.to_owned(),
             Integer(v) => v.to_string(),
             Decimal(v) => v.to_string(),
+            FieldRef(field) => format!("field:{}", field.to_string()),
+            Range(begin, end) => format!("{}..{}", begin, end),
         }
     }
 }
@@ -438,6 +1309,27 @@ impl GetSingleValue for Value {
     }
 }
 
+impl RefADecimal for Value {
+    fn ref_a_decimal(&self) -> Result<&f64> {
+        match self {
+            Value::Decimal(v) => Ok(v),
+            _ => Err(Error::NotADecimal {
+                value: self.clone(),
+            }),
+        }
+    }
+}
+
+impl RefADecimal for Values {
+    fn ref_a_decimal(&self) -> Result<&f64> {
+        if let Some(first) = self.first() {
+            first.ref_a_decimal()
+        } else {
+            Err(Error::RefValueInEmptyList)
+        }
+    }
+}
+
 impl GetSingleValue for Values {
     fn get_a_str_ref(&self) -> Result<&str> {
         if let Some(first) = self.first() {
@@ -478,53 +1370,176 @@ impl Cont {
         operator_str: String,
         value: Values,
     ) -> Result<Self> {
-        let operator =
-            Operator::from_str(operator_str.as_str()).map_err(|_| Error::UnknownOperator {
-                operator: operator_str.to_owned(),
-            })?;
+        let operator = Operator::try_from(operator_str.as_str())?;
+        let field = Field::try_from(field_str.as_str())?;
 
-        let field = Field::from_str(field_str.as_str()).map_err(|_| Error::UnknownField {
-            field: field_str.to_owned(),
-        })?;
+        Self::with_operator(is_negative, field, operator, value)
+    }
 
-        let operators = FIELD_OPERATORS
-            .get(&field)
-            .copied()
-            // 没有注册运算符列表，表示字段未启用。
-            .ok_or(Error::FieldNotEndabled { field })?;
+    /// 从字段和运算符构建条件，同时检查运算符是否支持。
+    pub fn with_operator(
+        is_negative: bool,
+        field: Field,
+        operator: Operator,
+        value: Values,
+    ) -> Result<Self> {
+        let operators = field_operators(&field);
 
         // 检查运算符是否支持。
         if !operators.contains(&operator) {
-            return Err(Error::UnsupportedOperator { field, operator });
+            return Err(Error::UnsupportedFieldOperatorSuggestion {
+                field,
+                operator,
+                supported: operators.to_vec(),
+            });
         }
 
-        Ok(Cont {
-            is_negative,
-            field,
-            operator: Some(operator),
-            value: Some(value),
-        })
-    }
+        // 检查字段引用是否合法：仅支持在可比较大小的字段之间使用。
+        for v in &value {
+            if let Value::FieldRef(ref_field) = v {
+                if !is_comparable(&field) || !is_comparable(ref_field) {
+                    return Err(Error::InvalidValue {
+                        value: v.to_string(),
+                        field: field.to_string(),
+                    });
+                }
+            }
+        }
 
-    pub fn single_field(is_negative: bool, field_str: String) -> Result<Self> {
-        let field = Field::from_str(field_str.as_str()).map_err(|_| Error::UnknownField {
-            field: field_str.to_owned(),
-        })?;
+        // 检查范围是否合法：仅支持 `in` 和 `not_in`，且起点不能大于终点。
+        for v in &value {
+            if let Value::Range(begin, end) = v {
+                if !matches!(operator, Operator::In | Operator::NotIn) || begin > end {
+                    return Err(Error::InvalidValue {
+                        value: v.to_string(),
+                        field: field.to_string(),
+                    });
+                }
+            }
+        }
 
-        let _operators = FIELD_OPERATORS
-            .get(&field)
-            .copied()
-            // 没有注册运算符列表，表示字段未启用。
-            .ok_or(Error::FieldNotEndabled { field })?;
+        // 检查值的数量：`between` 只接受两个值。
+        if operator == Operator::Between && value.len() != 2 {
+            return Err(Error::RangeRequiresTwoValues { count: value.len() });
+        }
 
-        Ok(Cont {
-            is_negative,
+        // 检查值的数量：`contains` 只接受单个值。
+        if operator == Operator::Contains && value.len() != 1 {
+            return Err(Error::SingleValueRequired {
+                field,
+                operator,
+                count: value.len(),
+            });
+        }
+
+        // 检查值是否合法：小数必须是有限的（过大的小数字面量会溢出为无穷大）。
+        for v in &value {
+            if let Value::Decimal(decimal) = v {
+                if !decimal.is_finite() {
+                    return Err(Error::InvalidValue {
+                        value: v.to_string(),
+                        field: field.to_string(),
+                    });
+                }
+            }
+        }
+        if field == Field::MessageDiceEmoji {
+            for v in &value {
+                let emoji = v.get_a_str_ref()?;
+                if !DICE_EMOJIS.contains(&emoji) {
+                    return Err(Error::InvalidValue {
+                        value: emoji.to_owned(),
+                        field: field.to_string(),
+                    });
+                }
+            }
+        }
+
+        // 检查 `in` 和 `not_in` 的值类型并预先构建集合。字段引用在匹配时才能解析，此时不预先构建。
+        let value_set = if matches!(operator, Operator::In | Operator::NotIn)
+            && !value.iter().any(|v| matches!(v, Value::FieldRef(_)))
+        {
+            Some(build_value_set(&field, &value)?)
+        } else {
+            None
+        };
+
+        Ok(Cont {
+            is_negative,
+            field,
+            value_set,
+            patterns: if matches!(operator, Operator::AllMatches | Operator::AnyMatches) {
+                Some(Patterns::compile(&value)?)
+            } else {
+                None
+            },
+            operator: Some(operator),
+            value: Some(value),
+            is_optional: false,
+        })
+    }
+
+    pub fn single_field(is_negative: bool, field_str: String) -> Result<Self> {
+        Self::with_field(is_negative, Field::try_from(field_str.as_str())?)
+    }
+
+    /// 构建不具有运算符和值的条件。
+    pub fn with_field(is_negative: bool, field: Field) -> Result<Self> {
+        Ok(Cont {
+            is_negative,
             field,
             operator: None,
             value: None,
+            is_optional: false,
+            value_set: None,
+            patterns: None,
         })
     }
 
+    // 估算条件的匹配开销，数值越大开销越高。
+    fn estimated_cost(&self) -> u8 {
+        match self.operator {
+            None => 0,
+            Some(Operator::Eq)
+            | Some(Operator::Gt)
+            | Some(Operator::Lt)
+            | Some(Operator::Ge)
+            | Some(Operator::Le)
+            | Some(Operator::In)
+            | Some(Operator::NotIn)
+            | Some(Operator::Ieq)
+            | Some(Operator::Iin)
+            | Some(Operator::Between) => 1,
+            Some(Operator::Hd) | Some(Operator::Td) | Some(Operator::Contains) => 2,
+            Some(Operator::Any) | Some(Operator::All) | Some(Operator::EqSet) => 3,
+            Some(Operator::AllMatches) | Some(Operator::AnyMatches) => 4,
+        }
+    }
+
+    // 条件所限定的数值范围。不是数值比较条件时返回 `None`。
+    fn numeric_range(&self) -> Option<NumericRange> {
+        if self.is_negative || self.is_optional || !is_comparable(&self.field) {
+            return None;
+        }
+        let value = match self.value.as_deref() {
+            Some([value @ Value::Integer(_)]) | Some([value @ Value::Decimal(_)]) => {
+                value.get_a_decimal().ok()?
+            }
+            _ => return None,
+        };
+
+        let (lower, upper) = match self.operator? {
+            Operator::Eq => ((value, true), (value, true)),
+            Operator::Gt => ((value, false), (f64::INFINITY, false)),
+            Operator::Ge => ((value, true), (f64::INFINITY, false)),
+            Operator::Lt => ((f64::NEG_INFINITY, false), (value, false)),
+            Operator::Le => ((f64::NEG_INFINITY, false), (value, true)),
+            _ => return None,
+        };
+
+        Some(NumericRange { lower, upper })
+    }
+
     fn operator(&self) -> Result<&Operator> {
         if let Some(operator) = &self.operator {
             Ok(operator)
@@ -533,6 +1548,24 @@ impl Cont {
         }
     }
 
+    // 值的集合。未预先准备时临时构建。
+    fn value_set(&self) -> Result<Cow<'_, ValueSet>> {
+        if let Some(value_set) = &self.value_set {
+            Ok(Cow::Borrowed(value_set))
+        } else {
+            Ok(Cow::Owned(build_value_set(&self.field, self.value()?)?))
+        }
+    }
+
+    // 编译的正则表达式。未预先编译时临时编译。
+    fn patterns(&self) -> Result<Cow<'_, Patterns>> {
+        if let Some(patterns) = &self.patterns {
+            Ok(Cow::Borrowed(patterns))
+        } else {
+            Ok(Cow::Owned(Patterns::compile(self.value()?)?))
+        }
+    }
+
     fn value(&self) -> Result<&Values> {
         if let Some(value) = &self.value {
             Ok(value)
@@ -543,30 +1576,527 @@ impl Cont {
 }
 
 impl Matcher {
-    pub fn match_message(&mut self, message: &Message) -> Result<bool> {
-        self.loop_match(message, 0)
+    /// 匹配消息。参考 [`Evaluator`](struct.Evaluator.html)。
+    pub fn match_message(&self, message: &Message) -> Result<bool> {
+        Evaluator::new(self).match_message(message)
+    }
+
+    /// 匹配消息并返回详细的匹配结果。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text all {"承接" "广告"})"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("承接各类广告")),
+    ///     ..Default::default()
+    /// };
+    /// let result = matcher.match_message_detailed(&message)?;
+    ///
+    /// assert!(result.matched);
+    /// assert_eq!(Some(1), result.decisive_group);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn match_message_detailed(&self, message: &Message) -> Result<MatchResult> {
+        Evaluator::new(self).match_message_detailed(message)
     }
 
-    fn loop_match(&mut self, message: &Message, position: usize) -> Result<bool> {
-        if position > 0 && self.is_last_match {
-            return Ok(true);
+    /// 匹配消息并报告导致匹配成功的条件组和条件，匹配失败时返回 `None`。参考 [`MatchReport`](struct.MatchReport.html)。
+    ///
+    /// 条件组的选择与 [`match_message_detailed`](#method.match_message_detailed) 相同，只会额外计算获胜组中的条件。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text all {"承接" "广告"} and not message.from.is_bot)"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("承接各类广告")),
+    ///     ..Default::default()
+    /// };
+    /// let report = matcher.match_message_detail(&message)?.unwrap();
+    ///
+    /// assert_eq!(1, report.group_index);
+    /// assert_eq!(vec![0, 1], report.matched);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn match_message_detail(&self, message: &Message) -> Result<Option<MatchReport>> {
+        let group_index = match self.match_message_detailed(message)?.decisive_group {
+            Some(group_index) => group_index,
+            None => return Ok(None),
+        };
+
+        let mut matched = vec![];
+        for (i, cont) in self.groups[group_index].iter().enumerate() {
+            if cont.match_with_options(message, &self.options)? {
+                matched.push(i);
+            }
         }
-        if position > (self.groups.len() - 1) {
-            return Ok(self.is_last_match);
+
+        Ok(Some(MatchReport {
+            group_index,
+            matched,
+        }))
+    }
+
+    /// 收集导致匹配成功的关键字，匹配失败时返回空列表。
+    ///
+    /// 关键字来自获胜组（以及共同前置条件）中成立的 `any`、`all` 和 `contains` 条件，只包含在目标内容中找到的值。
+    /// 取反的条件成立时意味着没有找到值，因此不会产生关键字。重复的关键字只保留一个。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩" "菠菜" "赌场"})"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("东南亚博彩招聘")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(vec![String::from("博彩")], matcher.collect_matches(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn collect_matches(&self, message: &Message) -> Result<Vec<String>> {
+        let group_index = match self.match_message_detailed(message)?.decisive_group {
+            Some(group_index) => group_index,
+            None => return Ok(vec![]),
+        };
+
+        let mut keywords: Vec<String> = vec![];
+        for cont in self.common_prefix.iter().chain(&self.groups[group_index]) {
+            if cont.is_negative || !cont.match_with_options(message, &self.options)? {
+                continue;
+            }
+            for keyword in cont.matched_letters(message, &self.options)? {
+                if !keywords.contains(&keyword) {
+                    keywords.push(keyword);
+                }
+            }
         }
 
-        let conts = unsafe { self.groups.get_unchecked(position) };
+        Ok(keywords)
+    }
 
-        let mut result = true;
-        for cont in conts {
-            if !cont.match_message(message)? {
-                result = false;
+    /// 计算消息的得分。
+    ///
+    /// 得分是所有匹配成功的条件组的权重之和，此方法不会因为某个组匹配成功而提前结束。
+    /// 未在 [`weights`](struct.Matcher.html#structfield.weights) 中设置权重的组，权重为 `1.0`。
+    /// 可选条件的加分参考 [`Cont::is_optional`](struct.Cont.html#structfield.is_optional)。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let mut matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text any {"招聘"})"#)?;
+    /// matcher.weights = vec![2.0, 0.5];
+    ///
+    /// let message = Message {
+    ///     text: Some(String::from("东南亚博彩招聘")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(2.5, matcher.score(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn score(&self, message: &Message) -> Result<f64> {
+        let mut score = 0.0;
+        if !self.match_conts(&self.common_prefix, message)? {
+            return Ok(score);
+        }
+
+        for (i, conts) in self.groups.iter().enumerate() {
+            if self.match_conts(conts, message)? {
+                let mut bonus = 0;
+                for cont in conts.iter().filter(|cont| cont.is_optional) {
+                    if cont.match_with_options(message, &self.options)? {
+                        bonus += 1;
+                    }
+                }
+
+                score += self.weights.get(i).copied().unwrap_or(1.0) * (1 + bonus) as f64;
+            }
+        }
+
+        Ok(score)
+    }
+
+    /// 使用样本消息试运行匹配器，逐个报告实际的匹配结果是否与预期一致。
+    ///
+    /// 样本由消息和预期的匹配结果构成，用于在启用规则前检验规则。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"})"#)?;
+    /// let message_text = |text: &str| Message {
+    ///     text: Some(String::from(text)),
+    ///     ..Default::default()
+    /// };
+    /// let (spam, ham, missed) = (
+    ///     message_text("东南亚博彩招聘"),
+    ///     message_text("你好"),
+    ///     message_text("菠菜招聘"),
+    /// );
+    ///
+    /// let outcomes = matcher.test_against(&[(&spam, true), (&ham, false), (&missed, true)]);
+    ///
+    /// assert!(outcomes[0].is_passed());
+    /// assert!(outcomes[1].is_passed());
+    /// assert!(!outcomes[2].is_passed());
+    /// assert_eq!(Ok(false), outcomes[2].actual);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn test_against(&self, samples: &[(&Message, bool)]) -> Vec<TestOutcome> {
+        let evaluator = Evaluator::new(self);
+
+        samples
+            .iter()
+            .map(|(message, expected)| TestOutcome {
+                expected: *expected,
+                actual: evaluator.match_message(message),
+            })
+            .collect()
+    }
+
+    /// 匹配消息并记录每一个条件的匹配结果，用于调试规则。
+    ///
+    /// 与 [`match_message`](#method.match_message) 不同，此方法会匹配全部的条件（包括可选条件），不会提前结束。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text all {"承接" "广告" "推广"})"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("承接各类广告")),
+    ///     ..Default::default()
+    /// };
+    /// let trace = matcher.explain(&message)?;
+    ///
+    /// assert!(!trace.is_matched);
+    /// assert_eq!(Some(2), trace.conts[0].count());
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn explain(&self, message: &Message) -> Result<Trace> {
+        let mut conts = vec![];
+        let mut trace_conts = |group: Option<usize>, group_conts: &[Cont]| -> Result<bool> {
+            let mut is_group_matched = true;
+            let mut is_any_optional_matched = false;
+            for (index, cont) in group_conts.iter().enumerate() {
+                let (is_matched, is_absent) = cont.match_traced(message, &self.options)?;
+                if !is_matched && !cont.is_optional {
+                    is_group_matched = false;
+                }
+                if is_matched && cont.is_optional {
+                    is_any_optional_matched = true;
+                }
+                conts.push(ContTrace {
+                    group,
+                    index,
+                    field: cont.field,
+                    is_matched,
+                    is_absent,
+                    needle_count: cont.needle_count(message, &self.options)?,
+                });
+            }
+
+            if is_all_optional(group_conts) {
+                is_group_matched = is_any_optional_matched;
+            }
+
+            Ok(is_group_matched)
+        };
+
+        let is_prefix_matched = trace_conts(None, &self.common_prefix)?;
+        let mut is_any_group_matched = false;
+        for (i, group_conts) in self.groups.iter().enumerate() {
+            if trace_conts(Some(i), group_conts)? {
+                is_any_group_matched = true;
+            }
+        }
+
+        Ok(Trace {
+            is_matched: is_prefix_matched && is_any_group_matched,
+            conts,
+        })
+    }
+
+    /// 生成描述规则匹配目标的摘要，用于在管理界面中展示。
+    ///
+    /// 摘要使用英文描述，字段以 [`Field::description`](enum.Field.html#method.description) 表示。它不是规则语法，不能被重新解析。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"a" "b"} and not message.from.is_bot)"#)?;
+    ///
+    /// assert_eq!(
+    ///     r#"Matches if (text contains any of ["a", "b"] AND sender is not a bot)"#,
+    ///     matcher.summary()
+    /// );
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn summary(&self) -> String {
+        let describe_group = |conts: &[Cont]| {
+            let conts = conts.iter().map(Cont::summary).collect::<Vec<_>>();
+
+            format!("({})", conts.join(" AND "))
+        };
+        let groups = self
+            .groups
+            .iter()
+            .map(|conts| describe_group(conts))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        match (self.common_prefix.is_empty(), self.groups.is_empty()) {
+            (_, true) => String::from("Matches nothing"),
+            (true, false) => format!("Matches if {}", groups),
+            (false, false) => format!(
+                "Matches if {} AND ({})",
+                describe_group(&self.common_prefix),
+                groups
+            ),
+        }
+    }
+
+    /// 将匹配器还原为规则表达式。
+    ///
+    /// 条件组以 `or` 连接，组内的条件以 `and` 连接。共同前置条件会被放回每一个组中，因此结果总是不含嵌套的规则。
+    /// 重新编译结果得到的匹配器与原匹配器的匹配行为相同，但匹配选项不属于规则的一部分，不会被还原。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"a" "b"} and not message.from.is_bot) or (message.text.len gt 10)"#)?;
+    ///
+    /// assert_eq!(
+    ///     r#"(message.text any {"a" "b"} and not message.from.is_bot) or (message.text.len gt 10)"#,
+    ///     matcher.to_rule()
+    /// );
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn to_rule(&self) -> String {
+        self.groups
+            .iter()
+            .map(|conts| {
+                let conts = self
+                    .common_prefix
+                    .iter()
+                    .chain(conts)
+                    .map(Cont::to_rule)
+                    .collect::<Vec<_>>();
+
+                format!("({})", conts.join(" and "))
+            })
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+
+    /// 计算匹配成功的条件组数量。
+    ///
+    /// 与 [`score`](#method.score) 相同，此方法不会因为某个组匹配成功而提前结束，但不考虑权重和可选条件。
+    /// 可用于“消息至少违反 N 条独立规则”这类判断。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text any {"招聘"})"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("东南亚博彩招聘")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(2, matcher.count_matching_groups(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn count_matching_groups(&self, message: &Message) -> Result<usize> {
+        if !self.match_conts(&self.common_prefix, message)? {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        for conts in &self.groups {
+            if self.match_conts(conts, message)? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// 仅使用指定位置的条件组匹配消息，用于调试或单独测试规则片段。
+    ///
+    /// 提取出的共同前置条件（参考 [`factor_common_prefix`](#method.factor_common_prefix)）同样会参与匹配。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    /// use matchingram::models::Message;
+    ///
+    /// let matcher = compile_rule(r#"(message.text any {"博彩"}) or (message.text any {"招聘"})"#)?;
+    /// let message = Message {
+    ///     text: Some(String::from("高薪招聘")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(!matcher.match_group(0, &message)?);
+    /// assert!(matcher.match_group(1, &message)?);
+    /// assert!(matcher.match_group(2, &message).is_err());
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn match_group(&self, index: usize, message: &Message) -> Result<bool> {
+        let conts = self.groups.get(index).ok_or(Error::GroupIndexOutOfRange {
+            index,
+            len: self.groups.len(),
+        })?;
+
+        Ok(self.match_conts(&self.common_prefix, message)? && self.match_conts(conts, message)?)
+    }
+
+    /// 查找条件组中相互矛盾的条件。
+    ///
+    /// 仅检测同一组中同一数值字段的范围冲突（`eq`/`gt`/`ge`/`le`），存在矛盾的组永远不会匹配成功。
+    /// 取反的条件、可选条件和字段引用不参与检测。
+    ///
+    /// ```
+    /// use matchingram::compile_rule;
+    ///
+    /// let matcher = compile_rule(r#"(message.text.len gt 10 and message.text.len le 5) or (message.text.len gt 10)"#)?;
+    /// let contradictions = matcher.find_contradictions();
+    ///
+    /// assert_eq!(1, contradictions.len());
+    /// assert_eq!(0, contradictions[0].group);
+    /// assert_eq!((0, 1), contradictions[0].conts);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn find_contradictions(&self) -> Vec<Contradiction> {
+        let mut contradictions = vec![];
+
+        for (group, conts) in self.groups.iter().enumerate() {
+            // 一维区间两两相交则整体相交，因此只需两两比较。
+            for (i, a) in conts.iter().enumerate() {
+                let a_range = match a.numeric_range() {
+                    Some(range) => range,
+                    None => continue,
+                };
+                for (j, b) in conts.iter().enumerate().skip(i + 1) {
+                    if a.field != b.field {
+                        continue;
+                    }
+                    if let Some(b_range) = b.numeric_range() {
+                        if a_range.is_disjoint(&b_range) {
+                            contradictions.push(Contradiction {
+                                group,
+                                field: a.field,
+                                conts: (i, j),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        contradictions
+    }
+
+    /// 按照估算的开销从低到高重新排列每个组中的条件，令组能够更早地因条件不成立而结束匹配。
+    ///
+    /// 开销由低到高依次为：字段存在检查、数字比较和 `eq`/`in`、`hd`/`td`、`any`/`all`。开销相同的条件保持原有顺序。
+    /// 组内条件是 `and` 关系，满足交换律，因此重新排列不改变匹配结果。
+    /// 但条件存在错误（例如值的类型不正确）时，返回错误还是 `false` 可能因顺序而不同。
+    pub fn optimize(&mut self) {
+        self.common_prefix.sort_by_key(Cont::estimated_cost);
+        for conts in self.groups.iter_mut() {
+            conts.sort_by_key(Cont::estimated_cost);
+        }
+    }
+
+    /// 将所有条件组共同的前置条件提取出来，返回提取的条件数量。
+    ///
+    /// 提取后的条件保存在 [`common_prefix`](struct.Matcher.html#structfield.common_prefix) 中，匹配时只计算一次。
+    /// 这是一种优化手段，不改变匹配结果：`(A and B) or (A and C)` 等价于 `A and (B or C)`。
+    /// 仅当至少存在两个组，且所有组的首个条件都相同时才会提取。
+    pub fn factor_common_prefix(&mut self) -> usize {
+        let mut count = 0;
+
+        while self.groups.len() > 1 {
+            // 可选条件参与计分，不能提取。
+            let first = match self.groups[0].first() {
+                Some(first) if !first.is_optional => first,
+                _ => break,
+            };
+            if !self.groups[1..]
+                .iter()
+                .all(|conts| conts.first() == Some(first))
+            {
                 break;
             }
+            // 提取后只剩可选条件的组会要求至少一个可选条件成立，改变匹配结果。
+            if self.groups.iter().any(|conts| is_all_optional(&conts[1..])) {
+                break;
+            }
+
+            let cont = self.groups[0].remove(0);
+            // 其余组的首个条件与之相同，直接丢弃。
+            for conts in self.groups[1..].iter_mut() {
+                conts.remove(0);
+            }
+            self.common_prefix.push(cont);
+            count += 1;
         }
-        self.is_last_match = result;
-        self.loop_match(message, position + 1)
+
+        count
     }
+
+    // 匹配单个条件组，组内的条件之间是 `and` 关系。
+    // 可选条件不影响匹配结果，直接跳过。但全部由可选条件构成的组，要求至少一个可选条件成立。
+    fn match_conts(&self, conts: &[Cont], message: &Message) -> Result<bool> {
+        if is_all_optional(conts) {
+            for cont in conts {
+                if cont.match_with_options(message, &self.options)? {
+                    return Ok(true);
+                }
+            }
+
+            return Ok(false);
+        }
+
+        for cont in conts.iter().filter(|cont| !cont.is_optional) {
+            if !cont.match_with_options(message, &self.options)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+// 否定以 `is` 或 `has` 为谓语的字段描述。
+fn negate_statement(statement: &str) -> String {
+    if let Some(position) = statement.find(" is ") {
+        format!(
+            "{} is not {}",
+            &statement[..position],
+            &statement[position + 4..]
+        )
+    } else if let Some(position) = statement.find(" has ") {
+        format!(
+            "{} does not have {}",
+            &statement[..position],
+            &statement[position + 5..]
+        )
+    } else {
+        format!("not {}", statement)
+    }
+}
+
+// 组是否全部由可选条件构成。空的组不算。
+fn is_all_optional(conts: &[Cont]) -> bool {
+    !conts.is_empty() && conts.iter().all(|cont| cont.is_optional)
 }
 
 // 检查子字段是否为存在或为真。
@@ -590,281 +2120,670 @@ macro_rules! ufh {
     };
 }
 
+// 生成字段的运算符注册表、用户字段的种类和表，以及用户字段和带值字段的匹配函数，保证已注册的运算符都有对应的匹配实现。
+//
+// 用户字段的种类分为两类，`message.from`、`message.forward_from` 和 `message.via_bot` 共用：
+// - `user_values`：带值的种类，格式与 `values` 相同，字段值表达式中可使用用户。
+// - `user_flags`：不接受运算符和值的种类，格式为 `种类 => 表达式;`。用户不存在时视作不成立。
+//
+// 字段分为三类：
+// - `users`：用户字段，格式为 `字段 => 用户, 用户字段的种类;`，运算符由用户字段的种类决定。
+// - `flags`：不接受运算符和值的字段，在 `Cont::match_field` 中匹配。
+// - `values`：带值字段，格式为 `字段 => 字段值, [运算符, ...];`，字段值表达式中可使用 `ufh!` 宏处理不存在的字段，也可以使用条件和匹配选项。
+//   运算符默认调用同名的运算符方法，`运算符: 方法` 的形式可以为单个运算符指定其它方法。
+//
+// 生成的 `field_operators` 穷尽匹配所有字段，新增的字段未在此注册将无法通过编译。
+macro_rules! fields {
+    (
+        $cont:ident, $message:ident, $options:ident, $user:ident;
+        user_values { $($user_value:ident => $user_target:expr, [$($user_operator:ident $(: $user_method:ident)?),*];)* }
+        user_flags { $($user_flag:ident => $user_flag_expr:expr;)* }
+        users { $($user_field:ident => $source:ident, $kind:ident;)* }
+        flags { $($flag_field:ident),* $(,)? }
+        values { $($field:ident => $target:expr, [$($operator:ident $(: $method:ident)?),*];)* }
+    ) => {
+        // 字段支持的运算符。
+        fn field_operators(field: &Field) -> &'static [Operator] {
+            match field {
+                $(Field::$user_field => UserField::$kind.operators(),)*
+                $(Field::$flag_field => &[],)*
+                $(Field::$field => &[$(Operator::$operator),*],)*
+            }
+        }
+
+        // 用户字段的种类。
+        #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+        enum UserField {
+            $($user_value,)*
+            $($user_flag,)*
+        }
+
+        impl UserField {
+            // 支持的运算符。
+            fn operators(&self) -> &'static [Operator] {
+                match self {
+                    $(UserField::$user_value => &[$(Operator::$user_operator),*],)*
+                    $(UserField::$user_flag => &[],)*
+                }
+            }
+        }
+
+        // 匹配用户字段，返回未取反的结果。用户或字段不存在时返回 `Error::FalsyValueHosting`。
+        fn match_user_field($cont: &Cont, user: &Option<User>, user_field: UserField) -> Result<bool> {
+            match user_field {
+                // 用户不存在时不成立，与其它不带运算符的字段一致。
+                $(UserField::$user_flag => Ok(user.as_ref().is_some_and(|$user| $user_flag_expr)),)*
+                $(UserField::$user_value => {
+                    let $user = ufh!(user);
+                    let target = &$user_target;
+
+                    fields!(@dispatch $cont, target, [$($user_operator $(: $user_method)?),*])
+                })*
+            }
+        }
+
+        // 用户字段，以及它们所属的用户和用户字段的种类。
+        static USER_FIELDS: &[(Field, UserSource, UserField)] = &[
+            $((Field::$user_field, UserSource::$source, UserField::$kind),)*
+        ];
+
+        // 匹配带值字段，返回未取反的结果。字段不存在时返回 `Error::FalsyValueHosting`。
+        fn match_value_field($cont: &Cont, $message: &Message, $options: &MatchOptions) -> Result<bool> {
+            match $cont.field {
+                $(Field::$field => {
+                    let target = &$target;
+
+                    fields!(@dispatch $cont, target, [$($operator $(: $method)?),*])
+                })*
+                // 用户字段和不带值的字段在 `Cont::match_field` 中匹配，不会到达这里。
+                field => unreachable!("`{}` is not a value field", field.to_string()),
+            }
+        }
+    };
+    (@dispatch $cont:ident, $target:ident, [$($operator:ident $(: $method:ident)?),*]) => {
+        match $cont.operator()? {
+            $(Operator::$operator => fields!(@call $cont, $target, $operator $(: $method)?),)*
+            operator => Err(Error::UnsupportedOperator {
+                field: $cont.field,
+                operator: *operator,
+            }),
+        }
+    };
+    (@call $cont:ident, $target:ident, $operator:ident: $method:ident) => { $target.$method($cont.value()?) };
+    (@call $cont:ident, $target:ident, Eq) => { $target.eq_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Gt) => { $target.gt_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Lt) => { $target.lt_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Ge) => { $target.ge_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Le) => { $target.le_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Between) => { $target.between_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, In) => { $target.in_ope($cont.value_set()?.as_ref()) };
+    (@call $cont:ident, $target:ident, Ieq) => { $target.ieq_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Iin) => { $target.iin_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, NotIn) => { $target.not_in_ope($cont.value_set()?.as_ref()) };
+    (@call $cont:ident, $target:ident, Any) => { $target.any_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, All) => { $target.all_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Hd) => { $target.hd_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Td) => { $target.td_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, Contains) => { $target.contains_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, EqSet) => { $target.eq_set_ope($cont.value()?) };
+    (@call $cont:ident, $target:ident, AllMatches) => { $target.all_matches_ope($cont.patterns()?.as_ref()) };
+    (@call $cont:ident, $target:ident, AnyMatches) => { $target.any_matches_ope($cont.patterns()?.as_ref()) };
+}
+
+// 按条件的运算符规范化文本。文本不存在时返回 `Error::FalsyValueHosting`。
+fn normalize_present<'a>(
+    target: &'a Option<String>,
+    cont: &Cont,
+    options: &MatchOptions,
+) -> Result<Cow<'a, Option<String>>> {
+    if target.is_none() {
+        return Err(Error::FalsyValueHosting);
+    }
+
+    Ok(options.normalize(target, cont.operator()?))
+}
+
+fields! {
+    cont, message, options, user;
+    user_values {
+        Id => user.id, [Eq, In, NotIn, Gt, Lt, Ge, Le, Between];
+        FirstName => user.first_name, [Eq, Ieq, In, Iin, NotIn, Any, All, Hd, Td, Contains];
+        Username => ufh!(user.username), [Eq, Ieq, In, Iin, NotIn, Any, All, Hd, Td, Contains];
+    }
+    user_flags {
+        IsBot => user.is_bot;
+    }
+    users {
+        MessageFromId => From, Id;
+        MessageFromIsBot => From, IsBot;
+        MessageFromFirstName => From, FirstName;
+        MessageFromUsername => From, Username;
+        MessageForwardFromId => ForwardFrom, Id;
+        MessageForwardFromIsBot => ForwardFrom, IsBot;
+        MessageForwardFromFirstName => ForwardFrom, FirstName;
+        MessageForwardFromUsername => ForwardFrom, Username;
+        MessageViaBotId => ViaBot, Id;
+        MessageViaBotIsBot => ViaBot, IsBot;
+        MessageViaBotFirstName => ViaBot, FirstName;
+        MessageViaBotUsername => ViaBot, Username;
+    }
+    flags {
+        MessageForwardFromChat,
+        MessageReplyToMessage,
+        MessageTextHasInvisible,
+        MessageTextHasBidiControl,
+        MessageTextHasAmount,
+        MessageDocumentFileNameHasBidiControl,
+        MessageAnimation,
+        MessageAudio,
+        MessageDocument,
+        MessagePhoto,
+        MessageSticker,
+        MessageStickerIsAnimated,
+        MessageVideo,
+        MessageVoice,
+        MessageDice,
+        MessagePoll,
+        MessageVenue,
+        MessageLocation,
+        MessageNewChatMembers,
+        MessageLeftChatMember,
+        MessageNewChatTitle,
+        MessageNewChatPhoto,
+        MessagePinnedMessage,
+        MessageIsServiceMessage,
+        MessageIsCommand,
+        MessageIsTopicMessage,
+        MessageIsRepeat,
+        MessageTextIsBlank,
+    }
+    values {
+        MessageText => normalize_present(&message.text, cont, options)?, [Eq, In, NotIn, Any, All, Contains, EqSet, AllMatches, AnyMatches];
+        MessageTextLen => message.text, [Eq: eq_ope_for_content_len, Gt: gt_ope_for_content_len, Lt: lt_ope_for_content_len, Ge: ge_ope_for_content_len, Le: le_ope_for_content_len, Between: between_ope_for_content_len];
+        MessageTextMaxRun => text::max_run(ufh!(message.text)) as i64, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageCaption => normalize_present(&message.caption, cont, options)?, [Eq, In, NotIn, All, Any, Hd, Td, Contains, AllMatches, AnyMatches];
+        MessageCaptionLen => message.caption, [Eq: eq_ope_for_content_len, Gt: gt_ope_for_content_len, Lt: lt_ope_for_content_len, Ge: ge_ope_for_content_len, Le: le_ope_for_content_len, Between: between_ope_for_content_len];
+        // MIME 类型不区分大小写。
+        MessageDocumentMimeType => ufh!(message.document).mime_type, [Eq, Ieq, In, Iin, NotIn, Hd, Td, Contains: contains_ope_ignore_case];
+        MessageNewChatMembersUsernames => ufh!(message.new_chat_members)
+            .iter()
+            .filter_map(|member| member.username.clone())
+            .collect::<Vec<_>>(), [Any, All];
+        MessageAllText => options.normalize(&Some(message.all_text()), cont.operator()?).into_owned(), [Any, All, Contains];
+        MessageForwardAge => ufh!(message.date) - ufh!(message.forward_date), [Eq, Gt, Lt, Ge, Le, Between];
+        MessageFromLastName => ufh!(message.from).last_name, [Eq, In, NotIn, Any, All, Hd, Td, Contains];
+        MessageFromFullName => ufh!(message.from).full_name(), [Eq, In, NotIn, Any, All, Hd, Td, Contains];
+        MessageFromLanguageCode => ufh!(message.from).language_code, [Eq, Ieq, In, Iin, NotIn, Hd, Td];
+        MessageForwardFromChatId => ufh!(message.forward_from_chat).id, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageForwardFromChatType => ufh!(message.forward_from_chat).type_, [Eq, Ieq, In, Iin, NotIn];
+        MessageForwardFromChatTitle => ufh!(message.forward_from_chat).title, [Eq, Any, All, Hd, Td, Contains];
+        MessageAnimationDuration => ufh!(message.animation).duration, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageAnimationFileName => ufh!(message.animation).file_name, [Eq, Any, All, Hd, Td, Contains];
+        MessageAnimationMimeType => ufh!(message.animation).mime_type, [Eq, Ieq, In, Iin, NotIn, Hd, Td];
+        MessageAnimationFileSize => ufh!(message.animation).file_size, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageAudioDuration => ufh!(message.audio).duration, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageAudioPerformer => ufh!(message.audio).performer, [Eq, All, Any, Hd, Td, Contains];
+        MessageAudioTitle => ufh!(message.audio).title, [Eq, All, Any, Hd, Td, Contains];
+        MessageAudioMimeType => ufh!(message.audio).mime_type, [Eq, Ieq, In, Iin, NotIn, Hd, Td];
+        MessageAudioFileSize => ufh!(message.audio).file_size, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageDocumentFileName => ufh!(message.document).file_name, [Eq, All, Any, Hd, Td, Contains];
+        MessageDocumentFileSize => ufh!(message.document).file_size, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageStickerEmoji => options.strip_emoji_modifiers(&ufh!(message.sticker).emoji), [Eq, In, NotIn];
+        MessageStickerSetName => ufh!(message.sticker).set_name, [Eq, All, Any, Hd, Td, Contains];
+        MessageVideoDuration => ufh!(message.video).duration, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageVideoMimeType => ufh!(message.video).mime_type, [Eq, Ieq, In, Iin, NotIn, Hd, Td];
+        MessageVideoFileSize => ufh!(message.video).file_size, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageVoiceDuration => ufh!(message.voice).duration, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageVoiceMimeType => ufh!(message.voice).mime_type, [Eq, Ieq, In, Iin, NotIn, Hd, Td];
+        MessageVoiceFileSize => ufh!(message.voice).file_size, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageDiceEmoji => options.strip_emoji_modifiers(&Some(ufh!(message.dice).emoji.clone())).into_owned(), [Eq, In, NotIn];
+        MessagePollType => {
+            let type_ = &ufh!(message.poll).type_;
+            // 缺失或为空的类型视作字段不存在。
+            if type_.is_empty() {
+                return Err(Error::FalsyValueHosting);
+            }
+            type_
+        }, [Eq, In, NotIn];
+        MessageVenueTitle => ufh!(message.venue).title, [Eq, All, Any, Hd, Td, Contains];
+        MessageVenueAddress => ufh!(message.venue).address, [Eq, All, Any, Hd, Td, Contains];
+        MessageLocationLongitude => ufh!(message.location).longitude, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageLocationLatitude => ufh!(message.location).latitude, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageMessageThreadId => ufh!(message.message_thread_id), [Eq, Gt, Lt, Ge, Le, Between];
+        MessageId => message.message_id, [Eq, In, NotIn, Gt, Lt, Ge, Le, Between];
+        MessageForwardDate => ufh!(message.forward_date), [Eq, Gt, Lt, Ge, Le, Between];
+        MessageChatId => ufh!(message.chat).id, [Eq, In, NotIn, Gt, Lt, Ge, Le, Between];
+        MessageReplyToMessageFromId => ufh!(ufh!(message.reply_to_message).from).id, [Eq, In, NotIn, Gt, Lt, Ge, Le, Between];
+        MessageReplyToMessageFromUsername => ufh!(ufh!(message.reply_to_message).from).username, [Eq, In, NotIn];
+        MessageTextMarkdown => message.text_markdown(), [Eq, Any, All, Hd, Td, Contains];
+        MessageMentions => message.mentions(), [Any, In, NotIn];
+        MessageTextUtf16Len => ufh!(message.text).encode_utf16().count() as i64, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageTextUpperRatio => text::upper_ratio(ufh!(message.text)) as i64, [Eq, Gt, Lt, Ge, Le, Between];
+        MessageTextMaxAmount => {
+            let max_amount = text::max_amount(ufh!(message.text));
+            *ufh!(max_amount)
+        }, [Eq, Gt, Lt, Ge, Le, Between];
+    }
+}
+
+// 消息中的用户。
+#[derive(Debug, Copy, Clone)]
+enum UserSource {
+    From,
+    ForwardFrom,
+    ViaBot,
+}
+
+impl UserSource {
+    fn user<'a>(&self, message: &'a Message) -> &'a Option<User> {
+        match self {
+            UserSource::From => &message.from,
+            UserSource::ForwardFrom => &message.forward_from,
+            UserSource::ViaBot => &message.via_bot,
+        }
+    }
+}
+
+// 查找用户字段所属的用户和用户字段的种类。
+fn find_user_field(field: &Field) -> Option<(UserSource, UserField)> {
+    USER_FIELDS
+        .iter()
+        .find(|(user_field, _, _)| user_field == field)
+        .map(|(_, source, user_field)| (*source, *user_field))
+}
+
+// 由值列表构建 `in` 运算的集合。可比较大小的字段的值是整数，其余字段的值是字符串。
+fn build_value_set(field: &Field, values: &Values) -> Result<ValueSet> {
+    if is_comparable(field) {
+        ValueSet::from_integers(values)
+    } else {
+        ValueSet::from_letters(values)
+    }
+}
+
+// 字段是否可比较大小。
+fn is_comparable(field: &Field) -> bool {
+    field_operators(field).contains(&Operator::Gt)
+}
+
+// 获取可比较大小的字段的值。字段不存在时返回 `Error::FalsyValueHosting`。
+fn resolve_comparable(field: &Field, message: &Message) -> Result<Value> {
+    let chars_count = |data: &Option<String>| -> Result<Value> {
+        Ok(Value::Integer(ufh!(data).chars().count() as i64))
+    };
+
+    if let Some((source, UserField::Id)) = find_user_field(field) {
+        return Ok(Value::Integer(ufh!(source.user(message)).id));
+    }
+
+    match field {
+        Field::MessageForwardFromChatId => Ok(Value::Integer(ufh!(message.forward_from_chat).id)),
+        Field::MessageTextLen => chars_count(&message.text),
+        Field::MessageTextMaxRun => Ok(Value::Integer(text::max_run(ufh!(message.text)) as i64)),
+        Field::MessageTextUpperRatio => {
+            Ok(Value::Integer(text::upper_ratio(ufh!(message.text)) as i64))
+        }
+        Field::MessageTextMaxAmount => {
+            Ok(Value::Decimal(*ufh!(text::max_amount(ufh!(message.text)))))
+        }
+        Field::MessageTextUtf16Len => Ok(Value::Integer(
+            ufh!(message.text).encode_utf16().count() as i64
+        )),
+        Field::MessageAnimationDuration => {
+            Ok(Value::Integer(ufh!(message.animation).duration as i64))
+        }
+        Field::MessageAnimationFileSize => {
+            Ok(Value::Integer(*ufh!(ufh!(message.animation).file_size)))
+        }
+        Field::MessageAudioDuration => Ok(Value::Integer(ufh!(message.audio).duration as i64)),
+        Field::MessageAudioFileSize => Ok(Value::Integer(*ufh!(ufh!(message.audio).file_size))),
+        Field::MessageDocumentFileSize => {
+            Ok(Value::Integer(*ufh!(ufh!(message.document).file_size)))
+        }
+        Field::MessageVideoDuration => Ok(Value::Integer(ufh!(message.video).duration as i64)),
+        Field::MessageVideoFileSize => Ok(Value::Integer(*ufh!(ufh!(message.video).file_size))),
+        Field::MessageVoiceDuration => Ok(Value::Integer(ufh!(message.voice).duration as i64)),
+        Field::MessageVoiceFileSize => Ok(Value::Integer(*ufh!(ufh!(message.voice).file_size))),
+        Field::MessageCaptionLen => chars_count(&message.caption),
+        Field::MessageLocationLongitude => Ok(Value::Decimal(ufh!(message.location).longitude)),
+        Field::MessageLocationLatitude => Ok(Value::Decimal(ufh!(message.location).latitude)),
+        Field::MessageId => Ok(Value::Integer(message.message_id)),
+        Field::MessageMessageThreadId => Ok(Value::Integer(*ufh!(message.message_thread_id))),
+        Field::MessageForwardDate => Ok(Value::Integer(*ufh!(message.forward_date))),
+        Field::MessageChatId => Ok(Value::Integer(ufh!(message.chat).id)),
+        Field::MessageReplyToMessageFromId => {
+            Ok(Value::Integer(ufh!(ufh!(message.reply_to_message).from).id))
+        }
+        Field::MessageForwardAge => Ok(Value::Integer(
+            ufh!(message.date) - ufh!(message.forward_date),
+        )),
+        field => Err(Error::InvalidValue {
+            value: Value::FieldRef(*field).to_string(),
+            field: field.to_string(),
+        }),
+    }
+}
+
 impl Cont {
+    /// 使用默认选项匹配消息。
     pub fn match_message(&self, message: &Message) -> Result<bool> {
-        let unsupported_operator_err = || -> Result<Error> {
-            Ok(Error::UnsupportedOperator {
+        self.match_with_options(message, &Default::default())
+    }
+
+    /// 使用指定选项匹配消息。
+    pub fn match_with_options(&self, message: &Message, options: &MatchOptions) -> Result<bool> {
+        self.match_traced(message, options)
+            .map(|(is_matched, _)| is_matched)
+    }
+
+    // 匹配消息，同时返回字段是否不存在。
+    fn match_traced(&self, message: &Message, options: &MatchOptions) -> Result<(bool, bool)> {
+        match self.match_field_with_refs(message, options) {
+            Ok(no_negative) => {
+                if self.is_negative {
+                    Ok((!no_negative, false))
+                } else {
+                    Ok((no_negative, false))
+                }
+            }
+            Err(Error::FalsyValueHosting) => {
+                if options.require_all_fields {
+                    Ok((false, true))
+                } else if self.operator == Some(Operator::NotIn) {
+                    // 字段不存在时不属于值列表，`not_in` 成立。
+                    Ok((!self.is_negative, true))
+                } else if self.is_negative {
+                    Ok((true, true))
+                } else {
+                    Ok((false, true))
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // 在目标内容中找到的文字值，仅支持 `any`、`all` 和 `contains` 条件。参考 `Matcher::collect_matches`。
+    // 每个值单独构成一个条件进行匹配，因此与条件本身的匹配使用相同的字段和选项处理。
+    fn matched_letters(&self, message: &Message, options: &MatchOptions) -> Result<Vec<String>> {
+        if !matches!(
+            self.operator,
+            Some(Operator::Any) | Some(Operator::All) | Some(Operator::Contains)
+        ) {
+            return Ok(vec![]);
+        }
+
+        let mut letters = vec![];
+        for value in self.value()? {
+            if let Value::Letter(letter) = value {
+                let cont = Cont {
+                    is_negative: false,
+                    field: self.field,
+                    operator: self.operator,
+                    value: Some(vec![value.clone()]),
+                    is_optional: false,
+                    value_set: None,
+                    patterns: None,
+                };
+                if cont.match_with_options(message, options)? {
+                    letters.push(letter.clone());
+                }
+            }
+        }
+
+        Ok(letters)
+    }
+
+    // 条件的规则表达式。参考 `Matcher::to_rule`。
+    fn to_rule(&self) -> String {
+        let describe_value = |value: &Value| match value {
+            Value::Letter(v) => format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")),
+            // 小数必须包含小数点，否则会被解析为整数。
+            Value::Decimal(v) if v.fract() == 0.0 => format!("{}.0", v),
+            _ => value.to_string(),
+        };
+
+        let mut rule = String::new();
+        if self.is_negative {
+            rule.push_str("not ");
+        }
+        rule.push_str(&self.field.to_string());
+        if let Some(operator) = self.operator {
+            let values = self.value.as_deref().unwrap_or_default();
+            let value = match values {
+                [value] => describe_value(value),
+                _ => format!(
+                    "{{{}}}",
+                    values
+                        .iter()
+                        .map(describe_value)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+            };
+
+            rule.push_str(&format!(" {} {}", operator.to_string(), value));
+        }
+
+        rule
+    }
+
+    // 条件的摘要。参考 `Matcher::summary`。
+    fn summary(&self) -> String {
+        let values = self.value.as_deref().unwrap_or_default();
+        let describe_value = |value: &Value| match value {
+            Value::Letter(v) => format!("{:?}", v),
+            Value::FieldRef(field) => field.description().to_owned(),
+            _ => value.to_string(),
+        };
+        let list = format!(
+            "[{}]",
+            values
+                .iter()
+                .map(describe_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let single = values.first().map(describe_value).unwrap_or_default();
+
+        let (positive, negative) = match self.operator {
+            // 不带值的字段的描述本身就是陈述。
+            None if field_operators(&self.field).is_empty() => {
+                let description = self.field.description();
+
+                return self.optional_summary(if self.is_negative {
+                    negate_statement(description)
+                } else {
+                    description.to_owned()
+                });
+            }
+            None => (String::from("is set"), String::from("is not set")),
+            Some(Operator::Eq) => (
+                format!("equals {}", single),
+                format!("does not equal {}", single),
+            ),
+            Some(Operator::Ieq) => (
+                format!("equals {} ignoring case", single),
+                format!("does not equal {} ignoring case", single),
+            ),
+            Some(Operator::Gt) => (
+                format!("is greater than {}", single),
+                format!("is not greater than {}", single),
+            ),
+            Some(Operator::Lt) => (
+                format!("is less than {}", single),
+                format!("is not less than {}", single),
+            ),
+            Some(Operator::Ge) => (
+                format!("is at least {}", single),
+                format!("is not at least {}", single),
+            ),
+            Some(Operator::Le) => (
+                format!("is at most {}", single),
+                format!("is not at most {}", single),
+            ),
+            Some(Operator::In) => (
+                format!("is one of {}", list),
+                format!("is not one of {}", list),
+            ),
+            Some(Operator::Iin) => (
+                format!("is one of {} ignoring case", list),
+                format!("is not one of {} ignoring case", list),
+            ),
+            Some(Operator::NotIn) => (
+                format!("is none of {}", list),
+                format!("is one of {}", list),
+            ),
+            Some(Operator::Any) => (
+                format!("contains any of {}", list),
+                format!("contains none of {}", list),
+            ),
+            Some(Operator::All) => (
+                format!("contains all of {}", list),
+                format!("does not contain all of {}", list),
+            ),
+            Some(Operator::AllMatches) => (
+                format!("matches all of patterns {}", list),
+                format!("does not match all of patterns {}", list),
+            ),
+            Some(Operator::AnyMatches) => (
+                format!("matches any of patterns {}", list),
+                format!("matches none of patterns {}", list),
+            ),
+            Some(Operator::Contains) => (
+                format!("contains {}", single),
+                format!("does not contain {}", single),
+            ),
+            Some(Operator::Hd) => (
+                format!("starts with {}", single),
+                format!("does not start with {}", single),
+            ),
+            Some(Operator::Td) => (
+                format!("ends with {}", single),
+                format!("does not end with {}", single),
+            ),
+            Some(Operator::EqSet) => (
+                format!("has exactly the words {}", list),
+                format!("does not have exactly the words {}", list),
+            ),
+            Some(Operator::Between) => {
+                let range = format!(
+                    "{} and {}",
+                    single,
+                    values.get(1).map(describe_value).unwrap_or_default()
+                );
+
+                (
+                    format!("is between {}", range),
+                    format!("is not between {}", range),
+                )
+            }
+        };
+        self.optional_summary(format!(
+            "{} {}",
+            self.field.description(),
+            if self.is_negative { negative } else { positive }
+        ))
+    }
+
+    // 为可选条件的摘要加上前缀。
+    fn optional_summary(&self, summary: String) -> String {
+        if self.is_optional {
+            format!("optionally {}", summary)
+        } else {
+            summary
+        }
+    }
+
+    // 统计 `any`/`all` 条件中被包含的值的数量，其它条件返回 `None`。
+    fn needle_count(&self, message: &Message, options: &MatchOptions) -> Result<Option<usize>> {
+        if !matches!(self.operator, Some(Operator::Any) | Some(Operator::All)) {
+            return Ok(None);
+        }
+
+        let mut count = 0;
+        for value in self.value()? {
+            let cont = Cont {
+                is_negative: false,
                 field: self.field,
-                operator: *self.operator()?,
-            })
+                operator: Some(Operator::Any),
+                value: Some(vec![value.clone()]),
+                is_optional: false,
+                value_set: None,
+                patterns: None,
+            };
+            if cont.match_with_options(message, options)? {
+                count += 1;
+            }
+        }
+
+        Ok(Some(count))
+    }
+
+    // 解析值中的字段引用后再匹配字段。被引用的字段不存在时返回 `Error::FalsyValueHosting`。
+    fn match_field_with_refs(&self, message: &Message, options: &MatchOptions) -> Result<bool> {
+        let value = match &self.value {
+            Some(value) if value.iter().any(|v| matches!(v, Value::FieldRef(_))) => value,
+            _ => return self.match_field(message, options),
         };
 
-        let r = match self.field {
-            Field::MessageFromId => match self.operator()? {
-                Operator::Eq => ufh!(message.from).id.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.from).id.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.from).id.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.from).id.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageFromIsBot => Ok(child_is_truthy!(&message.from, is_bot)),
-            Field::MessageFromFirstName => match self.operator()? {
-                Operator::Eq => ufh!(message.from).first_name.eq_ope(self.value()?),
-                Operator::In => ufh!(message.from).first_name.in_ope(self.value()?),
-                Operator::Any => ufh!(message.from).first_name.any_ope(self.value()?),
-                Operator::All => ufh!(message.from).first_name.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.from).first_name.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageFromLastName => match self.operator()? {
-                Operator::Eq => ufh!(message.from).last_name.eq_ope(self.value()?),
-                Operator::In => ufh!(message.from).last_name.in_ope(self.value()?),
-                Operator::Any => ufh!(message.from).last_name.any_ope(self.value()?),
-                Operator::All => ufh!(message.from).last_name.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.from).last_name.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageFromFullName => match self.operator()? {
-                Operator::Eq => ufh!(message.from).full_name().eq_ope(self.value()?),
-                Operator::In => ufh!(message.from).full_name().in_ope(self.value()?),
-                Operator::Any => ufh!(message.from).full_name().any_ope(self.value()?),
-                Operator::All => ufh!(message.from).full_name().all_ope(self.value()?),
-                Operator::Hd => ufh!(message.from).full_name().hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageFromLanguageCode => match self.operator()? {
-                Operator::Eq => ufh!(message.from).language_code.eq_ope(self.value()?),
-                Operator::In => ufh!(message.from).language_code.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.from).language_code.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
+        let mut resolved = vec![];
+        for v in value {
+            resolved.push(match v {
+                Value::FieldRef(field) => resolve_comparable(field, message)?,
+                v => v.clone(),
+            });
+        }
+
+        let cont = Cont {
+            is_negative: self.is_negative,
+            field: self.field,
+            operator: self.operator,
+            value: Some(resolved),
+            is_optional: self.is_optional,
+            value_set: None,
+            patterns: self.patterns.clone(),
+        };
+
+        cont.match_field(message, options)
+    }
+
+    // 匹配字段，返回未取反的结果。字段不存在时返回 `Error::FalsyValueHosting`。
+    fn match_field(&self, message: &Message, options: &MatchOptions) -> Result<bool> {
+        if let Some((source, user_field)) = find_user_field(&self.field) {
+            return match_user_field(self, source.user(message), user_field);
+        }
+
+        match self.field {
             Field::MessageForwardFromChat => Ok(message.forward_from_chat.is_truthy()),
-            Field::MessageForwardFromChatId => match self.operator()? {
-                Operator::Eq => ufh!(message.forward_from_chat).id.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.forward_from_chat).id.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.forward_from_chat).id.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.forward_from_chat).id.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageForwardFromChatType => match self.operator()? {
-                Operator::Eq => ufh!(message.forward_from_chat).type_.eq_ope(self.value()?),
-                Operator::In => ufh!(message.forward_from_chat).type_.in_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageForwardFromChatTitle => match self.operator()? {
-                Operator::Eq => ufh!(message.forward_from_chat).title.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.forward_from_chat).title.any_ope(self.value()?),
-                Operator::All => ufh!(message.forward_from_chat).title.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.forward_from_chat).title.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageReplyToMessage => Ok(message.reply_to_message.is_truthy()),
-            Field::MessageText => match self.operator()? {
-                Operator::Eq => message.text.eq_ope(self.value()?),
-                Operator::In => message.text.in_ope(self.value()?),
-                Operator::Any => message.text.any_ope(self.value()?),
-                Operator::All => message.text.all_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageTextLen => match self.operator()? {
-                Operator::Eq => message.text.eq_ope_for_content_len(self.value()?),
-                Operator::Gt => message.text.gt_ope_for_content_len(self.value()?),
-                Operator::Ge => message.text.ge_ope_for_content_len(self.value()?),
-                Operator::Le => message.text.le_ope_for_content_len(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
+            Field::MessageTextHasInvisible => Ok(text::has_invisible(ufh!(message.text))),
+            Field::MessageTextHasBidiControl => Ok(text::has_bidi_control(ufh!(message.text))),
+            Field::MessageTextHasAmount => Ok(!text::amounts(ufh!(message.text)).is_empty()),
+            // 文本不存在时也视作空白。
+            Field::MessageTextIsBlank => Ok(message.text.as_deref().is_none_or(text::is_blank)),
+            Field::MessageIsRepeat => {
+                let history = ufh!(options.message_history);
+                let from = ufh!(message.from);
+
+                Ok(history.is_repeat(from.id, ufh!(message.text)))
+            }
+            Field::MessageDocumentFileNameHasBidiControl => Ok(text::has_bidi_control(ufh!(
+                ufh!(message.document).file_name
+            ))),
             Field::MessageAnimation => Ok(message.animation.is_truthy()),
-            Field::MessageAnimationDuration => match self.operator()? {
-                Operator::Eq => ufh!(message.animation).duration.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.animation).duration.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.animation).duration.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.animation).duration.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAnimationFileName => match self.operator()? {
-                Operator::Eq => ufh!(message.animation).file_name.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.animation).file_name.any_ope(self.value()?),
-                Operator::All => ufh!(message.animation).file_name.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.animation).file_name.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAnimationMimeType => match self.operator()? {
-                Operator::Eq => ufh!(message.animation).mime_type.eq_ope(self.value()?),
-                Operator::In => ufh!(message.animation).mime_type.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.animation).mime_type.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAnimationFileSize => match self.operator()? {
-                Operator::Eq => ufh!(message.animation).file_size.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.animation).file_size.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.animation).file_size.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.animation).file_size.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageAudio => Ok(message.audio.is_truthy()),
-            Field::MessageAudioDuration => match self.operator()? {
-                Operator::Eq => ufh!(message.audio).duration.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.audio).duration.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.audio).duration.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.audio).duration.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAudioPerformer => match self.operator()? {
-                Operator::Eq => ufh!(message.audio).performer.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.audio).performer.any_ope(self.value()?),
-                Operator::All => ufh!(message.audio).performer.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.audio).performer.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAudioMimeType => match self.operator()? {
-                Operator::Eq => ufh!(message.audio).mime_type.eq_ope(self.value()?),
-                Operator::In => ufh!(message.audio).mime_type.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.audio).mime_type.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageAudioFileSize => match self.operator()? {
-                Operator::Eq => ufh!(message.audio).file_size.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.audio).file_size.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.audio).file_size.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.audio).file_size.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageDocument => Ok(message.document.is_truthy()),
-            Field::MessageDocumentFileName => match self.operator()? {
-                Operator::Eq => ufh!(message.document).file_name.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.document).file_name.any_ope(self.value()?),
-                Operator::All => ufh!(message.document).file_name.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.document).file_name.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageDocumentMimeType => match self.operator()? {
-                Operator::Eq => ufh!(message.document).mime_type.eq_ope(self.value()?),
-                Operator::In => ufh!(message.document).mime_type.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.document).mime_type.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageDocumentFileSize => match self.operator()? {
-                Operator::Eq => ufh!(message.document).file_size.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.document).file_size.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.document).file_size.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.document).file_size.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessagePhoto => Ok(message.photo.is_truthy()),
             Field::MessageSticker => Ok(message.sticker.is_truthy()),
             Field::MessageStickerIsAnimated => {
                 Ok(child_is_truthy!(&message.sticker, is_animated).is_truthy())
             }
-            Field::MessageStickerEmoji => match self.operator()? {
-                Operator::Eq => ufh!(message.sticker).emoji.eq_ope(self.value()?),
-                Operator::In => ufh!(message.sticker).emoji.in_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageStickerSetName => match self.operator()? {
-                Operator::Eq => ufh!(message.sticker).set_name.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.sticker).set_name.any_ope(self.value()?),
-                Operator::All => ufh!(message.sticker).set_name.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.sticker).set_name.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageVideo => Ok(message.video.is_truthy()),
-            Field::MessageVideoDuration => match self.operator()? {
-                Operator::Eq => ufh!(message.video).duration.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.video).duration.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.video).duration.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.video).duration.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageVideoMimeType => match self.operator()? {
-                Operator::Eq => ufh!(message.video).mime_type.eq_ope(self.value()?),
-                Operator::In => ufh!(message.video).mime_type.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.video).mime_type.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageVideoFileSize => match self.operator()? {
-                Operator::Eq => ufh!(message.video).file_size.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.video).file_size.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.video).file_size.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.video).file_size.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageVoice => Ok(message.voice.is_truthy()),
-            Field::MessageVoiceDuration => match self.operator()? {
-                Operator::Eq => ufh!(message.voice).duration.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.voice).duration.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.voice).duration.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.voice).duration.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageVoiceMimeType => match self.operator()? {
-                Operator::Eq => ufh!(message.voice).mime_type.eq_ope(self.value()?),
-                Operator::In => ufh!(message.voice).mime_type.in_ope(self.value()?),
-                Operator::Hd => ufh!(message.voice).mime_type.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageVoiceFileSize => match self.operator()? {
-                Operator::Eq => ufh!(message.voice).file_size.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.voice).file_size.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.voice).file_size.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.voice).file_size.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageCaption => match self.operator()? {
-                Operator::Eq => message.caption.eq_ope(self.value()?),
-                Operator::In => message.caption.in_ope(self.value()?),
-                Operator::Any => message.caption.any_ope(self.value()?),
-                Operator::All => message.caption.all_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageCaptionLen => match self.operator()? {
-                Operator::Eq => message.caption.eq_ope_for_content_len(self.value()?),
-                Operator::Gt => message.caption.gt_ope_for_content_len(self.value()?),
-                Operator::Ge => message.caption.ge_ope_for_content_len(self.value()?),
-                Operator::Le => message.caption.le_ope_for_content_len(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageDice => Ok(message.dice.is_truthy()),
-            Field::MessageDiceEmoji => match self.operator()? {
-                Operator::Eq => ufh!(message.dice).emoji.eq_ope(self.value()?),
-                Operator::In => ufh!(message.dice).emoji.in_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessagePoll => Ok(message.poll.is_truthy()),
-            Field::MessagePollType => match self.operator()? {
-                Operator::Eq => ufh!(message.poll).type_.eq_ope(self.value()?),
-                Operator::In => ufh!(message.poll).type_.in_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageVenue => Ok(message.venue.is_truthy()),
-            Field::MessageVenueTitle => match self.operator()? {
-                Operator::Eq => ufh!(message.venue).title.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.venue).title.any_ope(self.value()?),
-                Operator::All => ufh!(message.venue).title.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.venue).title.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageVenueAddress => match self.operator()? {
-                Operator::Eq => ufh!(message.venue).address.eq_ope(self.value()?),
-                Operator::Any => ufh!(message.venue).address.any_ope(self.value()?),
-                Operator::All => ufh!(message.venue).address.all_ope(self.value()?),
-                Operator::Hd => ufh!(message.venue).address.hd_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageLocation => Ok(message.location.is_truthy()),
-            Field::MessageLocationLongitude => match self.operator()? {
-                Operator::Eq => ufh!(message.location).longitude.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.location).longitude.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.location).longitude.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.location).longitude.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
-            Field::MessageLocationLatitude => match self.operator()? {
-                Operator::Eq => ufh!(message.location).latitude.eq_ope(self.value()?),
-                Operator::Gt => ufh!(message.location).latitude.gt_ope(self.value()?),
-                Operator::Ge => ufh!(message.location).latitude.ge_ope(self.value()?),
-                Operator::Le => ufh!(message.location).latitude.le_ope(self.value()?),
-                _ => Err(unsupported_operator_err()?),
-            },
             Field::MessageNewChatMembers => Ok(message.new_chat_members.is_truthy()),
             Field::MessageLeftChatMember => Ok(message.left_chat_member.is_truthy()),
             Field::MessageNewChatTitle => Ok(message.new_chat_title.is_truthy()),
@@ -889,26 +2808,8 @@ impl Cont {
             } else {
                 Ok(false)
             }
-            //
-            // field => Err(Error::FieldNotEndabled { field }),
-        };
-
-        match r {
-            Ok(no_negative) => {
-                if self.is_negative {
-                    Ok(!no_negative)
-                } else {
-                    Ok(no_negative)
-                }
-            }
-            Err(Error::FalsyValueHosting) => {
-                if self.is_negative {
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            }
-            e => e,
+            Field::MessageIsTopicMessage => Ok(message.is_topic_message.is_truthy()),
+            _ => match_value_field(self, message, options),
         }
     }
 }
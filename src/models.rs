@@ -6,12 +6,25 @@ use std::rc::Rc;
 /// This object represents a message.
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Message {
+    /// Unique message identifier inside this chat.
+    #[serde(default)]
+    pub message_id: i64,
+    /// Unique identifier of a message thread to which the message belongs; for supergroups only.
+    pub message_thread_id: Option<i64>,
     /// Sender, empty for messages sent to channels.
     pub from: Option<User>,
+    /// Date the message was sent in Unix time.
+    pub date: Option<i64>,
+    /// Conversation the message belongs to.
+    pub chat: Option<Chat>,
     /// For forwarded messages, sender of the original message.
     pub forward_from: Option<User>,
     /// For messages forwarded from channels, information about the original channel.
     pub forward_from_chat: Option<Chat>,
+    /// For forwarded messages, date the original message was sent in Unix time.
+    pub forward_date: Option<i64>,
+    /// True, if the message is sent to a forum topic.
+    pub is_topic_message: Option<bool>,
     /// For replies, the original message.
     /// Note that the Message object in this field will not contain further `reply_to_message` fields even if it itself is a reply.
     pub reply_to_message: Option<Rc<Message>>,
@@ -131,7 +144,7 @@ pub struct Animation {
     /// MIME type of the file as defined by sender.
     pub mime_type: Option<String>,
     /// File size.
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents an audio file to be treated as music by the Telegram clients.
@@ -146,7 +159,7 @@ pub struct Audio {
     /// MIME type of the file as defined by sender.
     pub mime_type: Option<String>,
     /// File size.
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents a general file (as opposed to photos, voice messages and audio files).
@@ -154,7 +167,7 @@ pub struct Audio {
 pub struct Document {
     pub file_name: Option<String>,
     pub mime_type: Option<String>,
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents one size of a photo or a file / sticker thumbnail.
@@ -162,7 +175,7 @@ pub struct Document {
 pub struct PhotoSize {
     pub width: i32,
     pub height: i32,
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents a sticker.
@@ -181,14 +194,14 @@ pub struct Sticker {
 pub struct Video {
     pub duration: i32,
     pub mime_type: Option<String>,
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents a video message (available in Telegram apps as of v.4.0).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VideoNote {
     pub duration: i32,
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents a voice note.
@@ -196,7 +209,7 @@ pub struct VideoNote {
 pub struct Voice {
     pub duration: i32,
     pub mime_type: Option<String>,
-    pub file_size: Option<i32>,
+    pub file_size: Option<i64>,
 }
 
 /// This object represents an animated emoji that displays a random value.
@@ -209,8 +222,8 @@ pub struct Dice {
 /// This object contains information about a poll.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Poll {
-    /// Poll type, currently can be “regular” or “quiz”.
-    #[serde(rename = "type")]
+    /// Poll type, currently can be “regular” or “quiz”. Empty if the type is omitted.
+    #[serde(rename = "type", default)]
     pub type_: String,
 }
 
@@ -229,6 +242,148 @@ pub struct Location {
     pub latitude: f64,
 }
 
+impl Message {
+    /// 以换行连接消息中的全部文本内容。
+    ///
+    /// 包括文本、说明、音频的表演者和标题、动画和文件的文件名、场所的标题和地址、
+    /// 转发来源的标题、新的群组标题，以及发送者、转发来源用户、bot 和新成员的用户名。
+    pub fn all_text(&self) -> String {
+        let mut parts = vec![self.text.as_ref(), self.caption.as_ref()];
+
+        if let Some(audio) = &self.audio {
+            parts.push(audio.performer.as_ref());
+            parts.push(audio.title.as_ref());
+        }
+        parts.push(self.animation.as_ref().and_then(|a| a.file_name.as_ref()));
+        parts.push(self.document.as_ref().and_then(|d| d.file_name.as_ref()));
+        if let Some(venue) = &self.venue {
+            parts.push(Some(&venue.title));
+            parts.push(Some(&venue.address));
+        }
+        parts.push(
+            self.forward_from_chat
+                .as_ref()
+                .and_then(|c| c.title.as_ref()),
+        );
+        parts.push(self.new_chat_title.as_ref());
+        for user in [&self.from, &self.forward_from, &self.via_bot]
+            .iter()
+            .copied()
+            .flatten()
+        {
+            parts.push(user.username.as_ref());
+        }
+        for user in self.new_chat_members.iter().flatten() {
+            parts.push(user.username.as_ref());
+        }
+
+        parts
+            .into_iter()
+            .flatten()
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 根据文本和实体还原出类似 Markdown 的文本。
+    ///
+    /// 格式实体会被还原为对应的标记（例如 `*粗体*`），`text_link` 实体会被还原为 `[文字](链接)`，
+    /// 因此可以匹配隐藏在文字中的链接。其余实体保持原样。没有文本时返回 `None`。
+    pub fn text_markdown(&self) -> Option<String> {
+        let text = self.text.as_ref()?;
+        let entities = match &self.entities {
+            Some(entities) => entities,
+            None => return Some(text.clone()),
+        };
+
+        // 插入的标记：（UTF-16 位置，排序键，标记）。
+        // 同一位置先插入结束标记再插入开始标记，结束标记由内向外，开始标记由外向内。
+        let mut markers = vec![];
+        for entity in entities {
+            if let Some((open, close)) = markdown_markers(entity) {
+                let begin = entity.offset as usize;
+                let end = begin + entity.length as usize;
+                markers.push((begin, (1, -entity.length as i64), open));
+                markers.push((end, (0, -entity.offset as i64), close));
+            }
+        }
+        markers.sort_by_key(|(pos, key, _)| (*pos, *key));
+
+        let mut markdown = String::with_capacity(text.len());
+        let mut markers = markers.into_iter().peekable();
+        let mut pos = 0;
+        for c in text.chars() {
+            while let Some((_, _, marker)) = markers.next_if(|(p, _, _)| *p <= pos) {
+                markdown.push_str(&marker);
+            }
+            markdown.push(c);
+            pos += c.len_utf16();
+        }
+        for (_, _, marker) in markers {
+            markdown.push_str(&marker);
+        }
+
+        Some(markdown)
+    }
+
+    /// 提取文本中提及的用户名（`@username`），不包含开头的 `@`，保持原有的大小写并去除重复。
+    ///
+    /// 仅提取前面不是字母、数字或下划线的 `@`，因此邮箱地址不会被视作提及。
+    pub fn mentions(&self) -> Vec<String> {
+        let mut mentions: Vec<String> = vec![];
+        let text = match &self.text {
+            Some(text) => text,
+            None => return mentions,
+        };
+
+        let is_username_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut last = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '@' && !last.is_some_and(is_username_char) {
+                let begin = i + 1;
+                let mut end = begin;
+                while let Some((j, _)) = chars.next_if(|(_, c)| is_username_char(*c)) {
+                    end = j + 1;
+                }
+                let username = &text[begin..end];
+                if !username.is_empty() && !mentions.iter().any(|m| m == username) {
+                    mentions.push(username.to_owned());
+                }
+                last = text[..end].chars().last();
+            } else {
+                last = Some(c);
+            }
+        }
+
+        mentions
+    }
+}
+
+// 实体对应的开始和结束标记。
+fn markdown_markers(entity: &MessageEntity) -> Option<(String, String)> {
+    let (open, close) = match entity.type_.as_str() {
+        "bold" => ("*".to_owned(), "*".to_owned()),
+        "italic" => ("_".to_owned(), "_".to_owned()),
+        "underline" => ("__".to_owned(), "__".to_owned()),
+        "strikethrough" => ("~".to_owned(), "~".to_owned()),
+        "spoiler" => ("||".to_owned(), "||".to_owned()),
+        "code" => ("`".to_owned(), "`".to_owned()),
+        "pre" => (
+            format!("```{}\n", entity.language.as_deref().unwrap_or_default()),
+            "\n```".to_owned(),
+        ),
+        "text_link" => ("[".to_owned(), format!("]({})", entity.url.as_ref()?)),
+        "text_mention" => (
+            "[".to_owned(),
+            format!("](tg://user?id={})", entity.user.as_ref()?.id),
+        ),
+        _ => return None,
+    };
+
+    Some((open, close))
+}
+
 impl User {
     pub fn full_name(&self) -> String {
         let mut full_name = self.first_name.clone();
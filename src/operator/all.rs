@@ -28,3 +28,17 @@ impl AllOperator<&Values> for Option<String> {
         }
     }
 }
+impl AllOperator<&Values> for [String] {
+    fn all_ope(&self, target: &Values) -> Result<bool> {
+        if self.is_empty() {
+            return Ok(false);
+        }
+        for item in self {
+            if !item.all_ope(target)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,23 @@
+/// 运算符 `all_matches` 的 trait 和相关实现。
+use crate::pattern::Patterns;
+use crate::result::Result;
+
+pub trait AllMatchesOperator<T> {
+    fn all_matches_ope(&self, target: T) -> Result<bool>;
+}
+
+impl AllMatchesOperator<&Patterns> for String {
+    fn all_matches_ope(&self, target: &Patterns) -> Result<bool> {
+        Ok(target.0.iter().all(|regex| regex.is_match(self)))
+    }
+}
+
+impl AllMatchesOperator<&Patterns> for Option<String> {
+    fn all_matches_ope(&self, target: &Patterns) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.all_matches_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
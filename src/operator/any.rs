@@ -28,3 +28,14 @@ impl AnyOperator<&Values> for Option<String> {
         }
     }
 }
+impl AnyOperator<&Values> for [String] {
+    fn any_ope(&self, target: &Values) -> Result<bool> {
+        for item in self {
+            if item.any_ope(target)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,23 @@
+/// 运算符 `any_matches` 的 trait 和相关实现。
+use crate::pattern::Patterns;
+use crate::result::Result;
+
+pub trait AnyMatchesOperator<T> {
+    fn any_matches_ope(&self, target: T) -> Result<bool>;
+}
+
+impl AnyMatchesOperator<&Patterns> for String {
+    fn any_matches_ope(&self, target: &Patterns) -> Result<bool> {
+        Ok(target.0.iter().any(|regex| regex.is_match(self)))
+    }
+}
+
+impl AnyMatchesOperator<&Patterns> for Option<String> {
+    fn any_matches_ope(&self, target: &Patterns) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.any_matches_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+/// 运算符 `between` 的 trait 和相关实现。
+use crate::error::Error;
+use crate::matches::{GetSingleValue, Value, Values};
+use crate::result::Result;
+
+/// 闭区间比较，值列表的两个值分别是下限和上限。下限大于上限时不会匹配任何值。
+pub trait BetweenOperator<T> {
+    fn between_ope(&self, target: T) -> Result<bool>;
+}
+pub trait BetweenOperatorForContentLen<T> {
+    fn between_ope_for_content_len(&self, target: T) -> Result<bool>;
+}
+
+// 取出值列表中的下限和上限。
+fn bounds(target: &Values) -> Result<(&Value, &Value)> {
+    match target.as_slice() {
+        [low, high] => Ok((low, high)),
+        [] => Err(Error::RefValueInEmptyList),
+        _ => Err(Error::RangeRequiresTwoValues {
+            count: target.len(),
+        }),
+    }
+}
+
+impl BetweenOperator<&Values> for i64 {
+    fn between_ope(&self, target: &Values) -> Result<bool> {
+        let (low, high) = bounds(target)?;
+
+        Ok(low.get_an_integer()? <= *self && *self <= high.get_an_integer()?)
+    }
+}
+
+impl BetweenOperator<&Values> for i32 {
+    fn between_ope(&self, target: &Values) -> Result<bool> {
+        (*self as i64).between_ope(target)
+    }
+}
+
+impl BetweenOperator<&Values> for Option<i64> {
+    fn between_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.between_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+impl BetweenOperator<&Values> for f64 {
+    fn between_ope(&self, target: &Values) -> Result<bool> {
+        let (low, high) = bounds(target)?;
+
+        Ok(low.get_a_decimal()? <= *self && *self <= high.get_a_decimal()?)
+    }
+}
+
+impl BetweenOperatorForContentLen<&Values> for String {
+    fn between_ope_for_content_len(&self, target: &Values) -> Result<bool> {
+        (self.chars().count() as i64).between_ope(target)
+    }
+}
+
+impl BetweenOperatorForContentLen<&Values> for Option<String> {
+    fn between_ope_for_content_len(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.between_ope_for_content_len(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+/// 运算符 `contains` 的 trait 和相关实现。
+use crate::matches::{GetSingleValue, Values};
+use crate::result::Result;
+
+pub trait ContainsOperator<T> {
+    fn contains_ope(&self, target: T) -> Result<bool>;
+    fn contains_ope_ignore_case(&self, target: T) -> Result<bool>;
+}
+
+impl ContainsOperator<&Values> for String {
+    fn contains_ope(&self, target: &Values) -> Result<bool> {
+        Ok(self.contains(target.get_a_str_ref()?))
+    }
+
+    fn contains_ope_ignore_case(&self, target: &Values) -> Result<bool> {
+        Ok(self
+            .to_lowercase()
+            .contains(&target.get_a_str_ref()?.to_lowercase()))
+    }
+}
+impl ContainsOperator<&Values> for Option<String> {
+    fn contains_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.contains_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn contains_ope_ignore_case(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.contains_ope_ignore_case(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
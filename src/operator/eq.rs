@@ -28,7 +28,7 @@ impl EqOperator<&Values> for f64 {
     }
 }
 
-impl EqOperator<&Values> for Option<i32> {
+impl EqOperator<&Values> for Option<i64> {
     fn eq_ope(&self, target: &Values) -> Result<bool> {
         if let Some(self_data) = self {
             self_data.eq_ope(target)
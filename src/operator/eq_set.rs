@@ -0,0 +1,34 @@
+/// 运算符 `eq_set` 的 trait 和相关实现。
+use std::collections::HashSet;
+
+use crate::matches::{GetSingleValue, Values};
+use crate::result::Result;
+
+/// 以空白分割内容得到单词集合，判断它是否与值列表构成的集合相等。
+///
+/// 比较的是集合，因此单词的顺序和重复次数都不影响结果。
+pub trait EqSetOperator<T> {
+    fn eq_set_ope(&self, target: T) -> Result<bool>;
+}
+
+impl EqSetOperator<&Values> for String {
+    fn eq_set_ope(&self, target: &Values) -> Result<bool> {
+        let words = self.split_whitespace().collect::<HashSet<_>>();
+        let mut values = HashSet::with_capacity(target.len());
+        for v in target {
+            values.insert(v.get_a_str_ref()?);
+        }
+
+        Ok(words == values)
+    }
+}
+
+impl EqSetOperator<&Values> for Option<String> {
+    fn eq_set_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.eq_set_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -21,7 +21,7 @@ impl GeOperator<&Values> for i32 {
     }
 }
 
-impl GeOperator<&Values> for Option<i32> {
+impl GeOperator<&Values> for Option<i64> {
     fn ge_ope(&self, target: &Values) -> Result<bool> {
         if let Some(self_data) = self {
             self_data.ge_ope(target)
@@ -21,7 +21,7 @@ impl GtOperator<&Values> for i32 {
     }
 }
 
-impl GtOperator<&Values> for Option<i32> {
+impl GtOperator<&Values> for Option<i64> {
     fn gt_ope(&self, target: &Values) -> Result<bool> {
         if let Some(self_data) = self {
             self_data.gt_ope(target)
@@ -0,0 +1,26 @@
+/// 运算符 `ieq` 的 trait 和相关实现。
+use crate::matches::{GetSingleValue, Values};
+use crate::result::Result;
+
+/// 忽略大小写的相等比较。
+///
+/// 比较前两侧都会经过 `str::to_lowercase` 转换，这是 Unicode 的简单大小写映射，不考虑语言环境（例如土耳其语的 `İ`）。
+pub trait IeqOperator<T> {
+    fn ieq_ope(&self, target: T) -> Result<bool>;
+}
+
+impl IeqOperator<&Values> for String {
+    fn ieq_ope(&self, target: &Values) -> Result<bool> {
+        Ok(self.to_lowercase() == target.get_a_str_ref()?.to_lowercase())
+    }
+}
+
+impl IeqOperator<&Values> for Option<String> {
+    fn ieq_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.ieq_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
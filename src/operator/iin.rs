@@ -0,0 +1,34 @@
+/// 运算符 `iin` 的 trait 和相关实现。
+use crate::matches::{GetSingleValue, Values};
+use crate::result::Result;
+
+/// 忽略大小写的属于比较。
+///
+/// 与 [`IeqOperator`](../ieq/trait.IeqOperator.html) 相同，大小写转换不考虑语言环境。
+pub trait IinOperator<T> {
+    fn iin_ope(&self, target: T) -> Result<bool>;
+}
+
+impl IinOperator<&Values> for String {
+    fn iin_ope(&self, target: &Values) -> Result<bool> {
+        let lowercase = self.to_lowercase();
+
+        for v in target {
+            if v.get_a_str_ref()?.to_lowercase() == lowercase {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl IinOperator<&Values> for Option<String> {
+    fn iin_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.iin_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
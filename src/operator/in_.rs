@@ -1,11 +1,101 @@
 /// 运算符 `in` 的 trait 和相关实现。
-use crate::matches::{GetSingleValue, Values};
+use std::collections::HashSet;
+
+use crate::error::Error;
+use crate::matches::{GetSingleValue, Value, Values};
 use crate::result::Result;
 
+/// 由值列表构建的集合，用于加速大列表的 `in` 运算。
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(
+    any(feature = "json", feature = "binary"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct ValueSet {
+    /// 列表中的整数。
+    pub integers: HashSet<i64>,
+    /// 列表中的字符串。
+    pub letters: HashSet<String>,
+    /// 列表中的整数范围。
+    pub ranges: Vec<(i64, i64)>,
+}
+
+impl ValueSet {
+    // 整数是否属于集合中的整数或范围。
+    fn contains_integer(&self, integer: &i64) -> bool {
+        self.integers.contains(integer)
+            || self
+                .ranges
+                .iter()
+                .any(|(begin, end)| (begin..=end).contains(&integer))
+    }
+
+    /// 由整数字段的值列表构建集合。值只能是整数或整数范围，否则返回 `Error::NotAnInteger`。
+    pub fn from_integers(values: &Values) -> Result<Self> {
+        let mut set = ValueSet::default();
+        for value in values {
+            match value {
+                Value::Integer(v) => {
+                    set.integers.insert(*v);
+                }
+                Value::Range(begin, end) => {
+                    set.ranges.push((*begin, *end));
+                }
+                _ => {
+                    return Err(Error::NotAnInteger {
+                        value: value.clone(),
+                    })
+                }
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// 由字符串字段的值列表构建集合。值只能是字符串，否则返回 `Error::NotAString`。
+    pub fn from_letters(values: &Values) -> Result<Self> {
+        let mut set = ValueSet::default();
+        for value in values {
+            match value {
+                Value::Letter(v) => {
+                    set.letters.insert(v.clone());
+                }
+                _ => {
+                    return Err(Error::NotAString {
+                        value: value.clone(),
+                    })
+                }
+            }
+        }
+
+        Ok(set)
+    }
+}
+
 pub trait InOperator<T> {
     fn in_ope(&self, target: T) -> Result<bool>;
 }
 
+impl InOperator<&Values> for i64 {
+    fn in_ope(&self, target: &Values) -> Result<bool> {
+        let mut r = false;
+
+        for v in target {
+            let is_contained = if let Value::Range(begin, end) = v {
+                (begin..=end).contains(&self)
+            } else {
+                v.get_an_integer()? == *self
+            };
+            if is_contained {
+                r = true;
+                break;
+            }
+        }
+
+        Ok(r)
+    }
+}
+
 impl InOperator<&Values> for String {
     fn in_ope(&self, target: &Values) -> Result<bool> {
         let mut r = false;
@@ -30,3 +120,31 @@ impl InOperator<&Values> for Option<String> {
         }
     }
 }
+
+impl InOperator<&ValueSet> for i64 {
+    fn in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(target.contains_integer(self))
+    }
+}
+
+impl InOperator<&ValueSet> for String {
+    fn in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(target.letters.contains(self))
+    }
+}
+
+impl InOperator<&ValueSet> for Option<String> {
+    fn in_ope(&self, target: &ValueSet) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.in_ope(target)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+impl InOperator<&ValueSet> for [String] {
+    fn in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(self.iter().any(|item| target.letters.contains(item)))
+    }
+}
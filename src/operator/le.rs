@@ -21,7 +21,7 @@ impl LeOperator<&Values> for i32 {
     }
 }
 
-impl LeOperator<&Values> for Option<i32> {
+impl LeOperator<&Values> for Option<i64> {
     fn le_ope(&self, target: &Values) -> Result<bool> {
         if let Some(self_data) = self {
             self_data.le_ope(target)
@@ -1,18 +1,31 @@
-use strum_macros::{EnumString, ToString};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use strum_macros::{EnumIter, EnumString, ToString};
+
+use crate::error::Error;
 
 pub mod all;
+pub mod all_matches;
 pub mod any;
+pub mod any_matches;
+pub mod between;
+pub mod contains;
 pub mod eq;
+pub mod eq_set;
 pub mod ge;
 pub mod gt;
 pub mod hd;
+pub mod ieq;
+pub mod iin;
 pub mod in_;
 pub mod le;
+pub mod lt;
+pub mod not_in;
 pub mod prelude;
 pub mod td;
 
 /// 运算符。
-#[derive(Debug, Eq, PartialEq, Copy, Clone, EnumString, ToString)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, EnumString, EnumIter, ToString)]
 #[strum(serialize_all = "snake_case")]
 pub enum Operator {
     /// 等于。
@@ -33,6 +46,49 @@ pub enum Operator {
     All,
     /// 头部相等。
     Hd,
-    // 尾部相等。
+    /// 尾部相等。
     Td,
+    /// 包含。
+    Contains,
+    /// 不属于任何一个。
+    NotIn,
+    /// 忽略大小写的等于。
+    Ieq,
+    /// 忽略大小写的属于其一。
+    Iin,
+    /// 在闭区间内。
+    Between,
+    /// 单词集合相等。
+    EqSet,
+    /// 全部模式都匹配。
+    AllMatches,
+    /// 任意一个模式匹配。
+    AnyMatches,
+}
+
+impl TryFrom<&str> for Operator {
+    type Error = Error;
+
+    fn try_from(operator: &str) -> Result<Self, Self::Error> {
+        Operator::from_str(operator).map_err(|_| Error::UnknownOperator {
+            operator: operator.to_owned(),
+        })
+    }
+}
+
+/// 以规则中的名称序列化运算符。
+#[cfg(any(feature = "json", feature = "binary"))]
+impl serde::Serialize for Operator {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(any(feature = "json", feature = "binary"))]
+impl<'de> serde::Deserialize<'de> for Operator {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let operator = String::deserialize(deserializer)?;
+
+        Operator::try_from(operator.as_str()).map_err(serde::de::Error::custom)
+    }
 }
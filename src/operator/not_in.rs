@@ -0,0 +1,59 @@
+/// 运算符 `not_in` 的 trait 和相关实现。
+use super::in_::{InOperator, ValueSet};
+use crate::matches::Values;
+use crate::result::Result;
+
+pub trait NotInOperator<T> {
+    fn not_in_ope(&self, target: T) -> Result<bool>;
+}
+
+impl NotInOperator<&Values> for i64 {
+    fn not_in_ope(&self, target: &Values) -> Result<bool> {
+        Ok(!self.in_ope(target)?)
+    }
+}
+
+impl NotInOperator<&Values> for String {
+    fn not_in_ope(&self, target: &Values) -> Result<bool> {
+        Ok(!self.in_ope(target)?)
+    }
+}
+
+// 值不存在时不属于值列表。
+impl NotInOperator<&Values> for Option<String> {
+    fn not_in_ope(&self, target: &Values) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.not_in_ope(target)
+        } else {
+            Ok(true)
+        }
+    }
+}
+
+impl NotInOperator<&ValueSet> for i64 {
+    fn not_in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(!self.in_ope(target)?)
+    }
+}
+
+impl NotInOperator<&ValueSet> for String {
+    fn not_in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(!self.in_ope(target)?)
+    }
+}
+
+impl NotInOperator<&ValueSet> for Option<String> {
+    fn not_in_ope(&self, target: &ValueSet) -> Result<bool> {
+        if let Some(self_data) = self {
+            self_data.not_in_ope(target)
+        } else {
+            Ok(true)
+        }
+    }
+}
+
+impl NotInOperator<&ValueSet> for [String] {
+    fn not_in_ope(&self, target: &ValueSet) -> Result<bool> {
+        Ok(!self.in_ope(target)?)
+    }
+}
@@ -1,11 +1,20 @@
 pub use super::{
     all::AllOperator,
+    all_matches::AllMatchesOperator,
     any::AnyOperator,
+    any_matches::AnyMatchesOperator,
+    between::{BetweenOperator, BetweenOperatorForContentLen},
+    contains::ContainsOperator,
     eq::{EqOperator, EqOperatorForContentLen},
+    eq_set::EqSetOperator,
     ge::{GeOperator, GeOperatorForContentLen},
     gt::{GtOperator, GtOperatorForContentLen},
     hd::HdOperator,
+    ieq::IeqOperator,
+    iin::IinOperator,
     in_::InOperator,
     le::{LeOperator, LeOperatorForContentLen},
+    lt::{LtOperator, LtOperatorForContentLen},
+    not_in::NotInOperator,
     td::TdOperator,
 };
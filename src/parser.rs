@@ -3,17 +3,24 @@
 //! 产生式：
 //! ```text
 //! 规则 -> 条件组 可选条件组列表 <EOF>
-//! 条件组 -> <(> 条件 可选条件列表 <)>
-//! 条件 -> 未取反条件 | <not> 未取反条件
-//! 未取反条件 -> <字段> <运算符> 值表示
+//! 条件组 -> <(> 或表达式 <)>
+//! 或表达式 -> 与表达式 | 与表达式 <or> 或表达式
+//! 与表达式 -> 条件 可选条件列表
+//! 条件 -> 未取反条件 | <not> 未取反条件 | 条件组 | <not> 条件组
+//! 未取反条件 -> <字段> <运算符> 值表示 | <字段>
 //! 值表示 -> 单值表示 | 多值表示
 //! 多值表示 -> <{> 单值表示 单值表示 ... <}>
-//! 单值表示 -> <"> <letter> <"> | <integer> | <decimal>
+//! 单值表示 -> <"> <letter> <"> | <integer> | <decimal> | <field_ref>
 //! 可选条件列表 -> <and> 条件 可选条件列表 | <空>
 //! 可选条件组列表 -> <or> 条件组 可选条件组列表 | <空>
 //! ```
 //!
-//! 当前的实现基于递归下降算法，语法制导直接生成 [`Matcher`](../matcher/struct.Matcher.html) 对象。
+//! 文字值中的 `\"` 和 `\\` 分别表示 `"` 和 `\`，其余的反斜杠保持原样。引号中的 `{` 和 `}` 不需要转义，例如 `any {"end}"}`。
+//! 转义只在 [`Dialect::V2`](../lexer/enum.Dialect.html#variant.V2) 中生效，`#lang v1` 的规则中反斜杠总是保持原样，与加入转义前的含义相同。
+//!
+//! 当前的实现基于递归下降算法。条件组可以嵌套，例如 `(A and (B or C))`，`and` 的优先级高于 `or`。
+//! 解析得到的表达式树会被展开为析取范式，即 [`Matcher`](../matcher/struct.Matcher.html) 对象使用的条件组列表：
+//! 上例等价于 `(A and B) or (A and C)`，条件组前的 `not` 会按德摩根定律分配到每个条件上。
 //!
 //! 一个使用案例：
 //! ```
@@ -27,7 +34,7 @@
 //! let input = rule.chars().collect::<Vec<_>>();
 //! let mut lexer = Lexer::new(&input);
 //! let parser = Parser::new(&mut lexer)?;
-//! let mut matcher = parser.parse()?;
+//! let matcher = parser.parse()?;
 //! // 两条典型的东南亚博彩招人消息。
 //! let message_text1 = format!("柬埔寨菠菜需要的来");
 //! let message_text2 = format!("东南亚博彩招聘");
@@ -54,14 +61,93 @@
 //! ```
 
 use super::error::Error;
-use super::lexer::{Lexer, Position, Token};
-use super::matches::{Cont, ContGroups, Matcher, Value};
+use super::lexer::{is_digit_separator, Dialect, Lexer, Position, Token, FIELD_REF_PREFIX};
+use super::matches::{Cont, ContGroups, Field, Matcher, Value};
+use super::operator::Operator;
 use super::result::Result;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
 use derivative::Derivative;
 
 type Input = Vec<Token>;
 
+/// 展开嵌套的条件组后允许得到的最大条件组数量。
+pub const MAX_EXPANDED_GROUPS: usize = 1024;
+
+// 表达式树。
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Cont(Cont),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    // 将否定分配到条件上。
+    fn negate(self) -> Expr {
+        match self {
+            Expr::And(exprs) => Expr::Or(exprs.into_iter().map(Expr::negate).collect()),
+            Expr::Or(exprs) => Expr::And(exprs.into_iter().map(Expr::negate).collect()),
+            Expr::Cont(mut cont) => {
+                cont.is_negative = !cont.is_negative;
+
+                Expr::Cont(cont)
+            }
+            Expr::Not(expr) => *expr,
+        }
+    }
+
+    // 展开为析取范式，即条件组列表。
+    fn into_groups(self) -> Result<ContGroups> {
+        let groups = match self {
+            Expr::Cont(cont) => vec![vec![cont]],
+            Expr::Not(expr) => expr.negate().into_groups()?,
+            Expr::Or(exprs) => {
+                let mut groups = vec![];
+                for expr in exprs {
+                    groups.append(&mut expr.into_groups()?);
+                }
+
+                groups
+            }
+            Expr::And(exprs) => {
+                let mut groups: ContGroups = vec![vec![]];
+                for expr in exprs {
+                    let expr_groups = expr.into_groups()?;
+                    let mut product = Vec::with_capacity(groups.len() * expr_groups.len());
+                    for group in &groups {
+                        for expr_group in &expr_groups {
+                            let mut conts = group.clone();
+                            conts.extend(expr_group.iter().cloned());
+                            product.push(conts);
+                        }
+                    }
+                    check_expanded_groups(product.len())?;
+
+                    groups = product;
+                }
+
+                groups
+            }
+        };
+        check_expanded_groups(groups.len())?;
+
+        Ok(groups)
+    }
+}
+
+fn check_expanded_groups(count: usize) -> Result<()> {
+    if count > MAX_EXPANDED_GROUPS {
+        Err(Error::TooManyExpandedGroups {
+            count,
+            max: MAX_EXPANDED_GROUPS,
+        })
+    } else {
+        Ok(())
+    }
+}
+
 /// 文法分析器。
 /// 解析 [`Lexer::Token`](../lexer/enum.Token.html) 序列生成 [`Matcher`](../matcher/struct.Matcher.html) 对象。
 #[derive(Derivative)]
@@ -78,6 +164,10 @@ pub struct Parser<'a> {
     pos: usize,
     // 当前的 token（current token）。
     pub ct: Option<&'a Token>,
+    // 字段别名。
+    aliases: HashMap<String, Field>,
+    // 规则的方言。
+    dialect: Dialect,
 }
 
 impl<'a> Parser<'a> {
@@ -96,81 +186,134 @@ impl<'a> Parser<'a> {
             positions: lexer.positions(),
             pos: 0,
             ct: input.get(0),
+            aliases: HashMap::new(),
+            dialect: lexer.dialect(),
         })
     }
 
+    /// 设置字段别名，解析时别名会被替换为对应的字段。
+    pub fn with_aliases(mut self, aliases: HashMap<String, Field>) -> Self {
+        self.aliases = aliases;
+
+        self
+    }
+
     /// 解析并得到匹配器对象。
     pub fn parse(mut self) -> Result<Matcher> {
-        let mut groups: ContGroups = vec![];
+        let mut exprs = vec![];
 
-        groups.push(self.parse_group()?);
+        exprs.push(self.parse_group()?);
 
         self.scan();
-        let mut optinal_groups = self.parse_optinal_group_list(vec![])?;
-        if optinal_groups.len() > 0 {
-            groups.append(&mut optinal_groups);
+        let mut optinal_exprs = self.parse_optinal_group_list(vec![])?;
+        if !optinal_exprs.is_empty() {
+            exprs.append(&mut optinal_exprs);
         }
 
         self.scan();
         if self.ct != Some(&Token::EOF) {
             let position = self.current_position()?;
             return Err(Error::ShouldEndHere {
-                column: position.begin,
+                line: position.line,
+                column: position.column + 1,
             });
         }
 
-        Ok(Matcher::new(groups))
+        Ok(Matcher::new(Expr::Or(exprs).into_groups()?))
     }
 
-    fn parse_group(&mut self) -> Result<Vec<Cont>> {
+    fn parse_group(&mut self) -> Result<Expr> {
         if self.ct != Some(&Token::OpenParenthesis) {
             let position = self.current_position()?;
             return Err(Error::ShouldOpenParenthesisHere {
-                column: position.begin,
+                line: position.line,
+                column: position.column + 1,
             });
         }
 
-        let mut conts = vec![];
-
         self.scan();
-        conts.push(self.parse_cont()?);
+        let expr = self.parse_or_expr()?;
 
         self.scan();
 
-        let mut optinal_conts = self.parse_optinal_cont_list(vec![])?;
-        if optinal_conts.len() > 0 {
-            conts.append(&mut optinal_conts);
-        }
-        self.scan();
-
         if self.ct != Some(&Token::CloseParenthesis) {
             let position = self.current_position()?;
 
             return Err(Error::ShouldCloseParenthesisHere {
-                column: position.begin,
+                line: position.line,
+                column: position.column + 1,
             });
         }
 
-        Ok(conts)
+        Ok(expr)
     }
 
-    fn parse_optinal_cont_list(&mut self, mut conts: Vec<Cont>) -> Result<Vec<Cont>> {
-        if conts.len() > 0 {
+    fn parse_or_expr(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.parse_and_expr()?];
+
+        self.scan();
+        while self.ct == Some(&Token::Or) {
+            self.scan();
+            exprs.push(self.parse_and_expr()?);
+            self.scan();
+        }
+        self.back();
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and_expr(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.parse_cont_expr()?];
+
+        self.scan();
+        let mut optinal_exprs = self.parse_optinal_cont_list(vec![])?;
+        if !optinal_exprs.is_empty() {
+            exprs.append(&mut optinal_exprs);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_optinal_cont_list(&mut self, mut exprs: Vec<Expr>) -> Result<Vec<Expr>> {
+        if !exprs.is_empty() {
             self.scan();
         }
 
         if self.ct != Some(&Token::And) {
             self.back();
-            return Ok(conts);
+            return Ok(exprs);
         }
 
         self.scan();
-        conts.push(self.parse_cont()?);
+        exprs.push(self.parse_cont_expr()?);
 
-        self.parse_optinal_cont_list(conts)
+        self.parse_optinal_cont_list(exprs)
     }
 
-    fn parse_optinal_group_list(&mut self, mut groups: ContGroups) -> Result<ContGroups> {
+    // 解析条件，包括嵌套的条件组。
+    fn parse_cont_expr(&mut self) -> Result<Expr> {
+        if self.ct == Some(&Token::Not)
+            && self.input.get(self.pos + 1) == Some(&Token::OpenParenthesis)
+        {
+            self.scan();
+
+            Ok(Expr::Not(Box::new(self.parse_group()?)))
+        } else if self.ct == Some(&Token::OpenParenthesis) {
+            self.parse_group()
+        } else {
+            Ok(Expr::Cont(self.parse_cont()?))
+        }
+    }
+
+    fn parse_optinal_group_list(&mut self, mut groups: Vec<Expr>) -> Result<Vec<Expr>> {
         if groups.len() > 0 {
             self.scan();
         }
@@ -198,31 +341,37 @@ impl<'a> Parser<'a> {
         if self.ct != Some(&Token::Field) {
             let position = self.current_position()?;
             return Err(Error::MissingField {
-                column: position.begin,
+                line: position.line,
+                column: position.column + 1,
             });
         }
-        let field = self.current_data()?.iter().collect();
+        let field = self.resolve_field(&self.current_data()?.iter().collect::<String>())?;
 
         self.scan();
 
-        if self.ct == Some(&Token::And) || self.ct == Some(&Token::CloseParenthesis) {
+        if self.ct == Some(&Token::And)
+            || self.ct == Some(&Token::Or)
+            || self.ct == Some(&Token::CloseParenthesis)
+        {
             self.back();
             // 单字段条件
-            Ok(Cont::single_field(is_negative, field)?)
+            Ok(Cont::with_field(is_negative, field)?)
         } else {
             // 多字段条件
             if self.ct != Some(&Token::Operator) {
                 let position = self.current_position()?;
                 return Err(Error::MissingOperator {
-                    column: position.begin,
+                    line: position.line,
+                    column: position.column + 1,
                 });
             }
-            let operator = self.current_data()?.iter().collect();
+            let operator =
+                Operator::try_from(self.current_data()?.iter().collect::<String>().as_str())?;
 
             self.scan();
             let value = self.parse_value()?;
 
-            Ok(Cont::new(is_negative, field, operator, value)?)
+            Ok(Cont::with_operator(is_negative, field, operator, value)?)
         }
     }
 
@@ -251,29 +400,57 @@ impl<'a> Parser<'a> {
         // 转换整数
         if self.ct == Some(&Token::Integer) {
             let value_data = self.at_data(self.pos)?;
-            let value_string = value_data.iter().collect::<String>();
+            let value_string = strip_digit_separators(value_data);
             let value_integer =
                 i64::from_str_radix(&value_string, 10).map_err(|_| Error::IntegerParseFailed {
-                    column: position.begin,
+                    line: position.line,
+                    column: position.column + 1,
                 })?;
 
             return Ok(Value::Integer(value_integer));
         }
 
+        // 转换范围。
+        if self.ct == Some(&Token::Range) {
+            let value_data = self.at_data(self.pos)?;
+            let value_string = strip_digit_separators(value_data);
+            let parse_failed = || Error::IntegerParseFailed {
+                line: position.line,
+                column: position.column + 1,
+            };
+            let (begin, end) = value_string.split_once("..").ok_or_else(parse_failed)?;
+            let parse_integer = |integer: &str| integer.parse::<i64>().map_err(|_| parse_failed());
+
+            return Ok(Value::Range(parse_integer(begin)?, parse_integer(end)?));
+        }
+
         // 转换小数。
         if self.ct == Some(&Token::Decimal) {
             let value_data = self.at_data(self.pos)?;
-            let string_value = value_data.iter().collect::<String>();
+            let string_value = strip_digit_separators(value_data);
             let decimal_value =
                 string_value
                     .parse::<f64>()
                     .map_err(|_| Error::DecimalParseFailed {
-                        column: position.begin,
+                        line: position.line,
+                        column: position.column + 1,
                     })?;
 
             return Ok(Value::Decimal(decimal_value));
         }
 
+        // 转换字段引用。
+        if self.ct == Some(&Token::FieldRef) {
+            let value_data = self.at_data(self.pos)?;
+            let field_string = value_data
+                .iter()
+                .skip(FIELD_REF_PREFIX.len())
+                .collect::<String>();
+            let field = self.resolve_field(&field_string)?;
+
+            return Ok(Value::FieldRef(field));
+        }
+
         if self.ct == Some(&Token::Quote)
             && self.input.get(self.pos + 1) == Some(&Token::Letter)
             && self.input.get(self.pos + 2) == Some(&Token::Quote)
@@ -282,14 +459,30 @@ impl<'a> Parser<'a> {
 
             self.scan_at(self.pos + 2);
 
-            return Ok(Value::Letter(value_data.iter().collect()));
+            let letter = if self.dialect.allows_escape() {
+                unescape(value_data)
+            } else {
+                value_data.iter().collect()
+            };
+
+            return Ok(Value::Letter(letter));
         }
 
         return Err(Error::ShouldValueHere {
-            column: position.begin,
+            line: position.line,
+            column: position.column + 1,
         });
     }
 
+    // 解析字段名称，优先使用别名。
+    fn resolve_field(&self, field_str: &str) -> Result<Field> {
+        if let Some(field) = self.aliases.get(field_str) {
+            Ok(*field)
+        } else {
+            Field::try_from(field_str)
+        }
+    }
+
     // 当前位置的 token 数据引用。
     fn current_data(&self) -> Result<&'a [char]> {
         self.at_data(self.pos)
@@ -336,3 +529,26 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+// 处理文字值中的转义字符。
+fn unescape(data: &[char]) -> String {
+    let mut letter = String::with_capacity(data.len());
+    let mut chars = data.iter().peekable();
+
+    while let Some(&c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&&next)) if matches!(next, '"' | '\\') => {
+                letter.push(next);
+                chars.next();
+            }
+            _ => letter.push(c),
+        }
+    }
+
+    letter
+}
+
+// 移除数字中的分组分隔符。
+fn strip_digit_separators(data: &[char]) -> String {
+    data.iter().filter(|c| !is_digit_separator(c)).collect()
+}
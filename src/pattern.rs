@@ -0,0 +1,117 @@
+//! 模式值（例如正则表达式）的安全检查。
+//!
+//! 规则可能由不受信任的用户编写，过长或存在嵌套量词的模式会导致匹配开销失控（ReDoS）。
+//! 接受模式值的运算符应该在编译条件时调用 [`check_pattern`](fn.check_pattern.html)。
+
+use super::error::Error;
+use super::matches::{GetSingleValue, Values};
+use super::result::Result;
+use regex::Regex;
+
+/// 模式的最大长度（字符数）。
+pub const MAX_PATTERN_LEN: usize = 256;
+
+/// 检查模式的长度和复杂度。
+///
+/// ```
+/// use matchingram::pattern::check_pattern;
+///
+/// assert!(check_pattern(r"^(ab)+c$").is_ok());
+/// assert!(check_pattern(r"^(a+)+$").is_err());
+/// ```
+pub fn check_pattern(pattern: &str) -> Result<()> {
+    let too_complex = |reason| Error::PatternTooComplex {
+        pattern: pattern.to_owned(),
+        reason,
+    };
+
+    if pattern.chars().count() > MAX_PATTERN_LEN {
+        return Err(too_complex("too long"));
+    }
+
+    // 每个未关闭的分组内是否存在量词。
+    let mut groups: Vec<bool> = vec![];
+    let mut chars = pattern.chars().peekable();
+    let mut is_inside_class = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => is_inside_class = true,
+            ']' => is_inside_class = false,
+            _ if is_inside_class => (),
+            '(' => groups.push(false),
+            ')' => {
+                let has_quantifier = groups.pop().unwrap_or(false);
+                let is_quantified = matches!(chars.peek(), Some('*') | Some('+') | Some('{'));
+
+                if has_quantifier && is_quantified {
+                    return Err(too_complex("nested quantifiers"));
+                }
+                if let Some(parent) = groups.last_mut() {
+                    *parent = *parent || has_quantifier || is_quantified;
+                }
+            }
+            '*' | '+' | '{' => {
+                if let Some(group) = groups.last_mut() {
+                    *group = true;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// 由值列表编译得到的正则表达式列表。
+///
+/// 比较时仅比较模式本身。
+#[derive(Debug, Clone)]
+pub struct Patterns(pub Vec<Regex>);
+
+impl Patterns {
+    /// 检查并编译值列表中的每一个模式。
+    ///
+    /// 模式不合法时返回 `Error::InvalidPattern`，其中包含模式在值列表中的位置（从 0 开始）。
+    ///
+    /// ```
+    /// use matchingram::matches::Value;
+    /// use matchingram::pattern::Patterns;
+    ///
+    /// assert!(Patterns::compile(&vec![Value::from_str(r"\d+"), Value::from_str("^a")]).is_ok());
+    /// assert!(Patterns::compile(&vec![Value::from_str(r"\d+"), Value::from_str("(a")]).is_err());
+    /// ```
+    pub fn compile(values: &Values) -> Result<Self> {
+        let mut regexes = Vec::with_capacity(values.len());
+        for (index, value) in values.iter().enumerate() {
+            let pattern = value.get_a_str_ref()?;
+            let invalid = |reason: String| Error::InvalidPattern {
+                index,
+                pattern: pattern.to_owned(),
+                reason,
+            };
+
+            check_pattern(pattern).map_err(|e| match e {
+                Error::PatternTooComplex { reason, .. } => invalid(reason.to_owned()),
+                e => e,
+            })?;
+            regexes.push(Regex::new(pattern).map_err(|e| invalid(e.to_string()))?);
+        }
+
+        Ok(Patterns(regexes))
+    }
+}
+
+impl PartialEq for Patterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|(a, b)| a.as_str() == b.as_str())
+    }
+}
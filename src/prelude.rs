@@ -0,0 +1,24 @@
+//! 常用的公开类型和函数。
+//!
+//! ```
+//! use matchingram::prelude::*;
+//!
+//! let matcher = compile_rule(r#"(message.text any {"Hello" "Bye"})"#)?;
+//! let message = Message {
+//!     text: Some(String::from("Hello telegram!")),
+//!     ..Default::default()
+//! };
+//!
+//! assert!(matcher_match(&matcher, &message)?);
+//! assert_eq!(Field::MessageText, matcher.groups[0][0].field);
+//! assert_eq!(Some(Operator::Any), matcher.groups[0][0].operator);
+//! # Ok::<(), Error>(())
+//! ```
+
+pub use super::error::Error;
+pub use super::matches::{Cont, Field, MatchOptions, Matcher, Value};
+pub use super::models::Message;
+pub use super::operator::Operator;
+pub use super::{compile_rule, matcher_match, rule_match};
+#[cfg(feature = "json")]
+pub use super::{matcher_match_json, rule_match_json};
@@ -0,0 +1,262 @@
+//! 规则集。
+//!
+//! 规则集由多条带标签的规则构成，通常从规则文件中加载。
+
+use super::error::Error;
+use super::matches::Matcher;
+use super::models::Message;
+use super::result::Result;
+
+/// 规则文件中默认的标签和规则的分隔符。
+pub const DEFAULT_DELIMITER: &str = ":";
+
+/// 规则文件中标签和优先级的分隔符。
+pub const PRIORITY_DELIMITER: char = '@';
+
+/// 带标签的规则。
+#[derive(Debug)]
+pub struct Rule {
+    /// 标签。
+    pub label: String,
+    /// 优先级，数值越大越优先。默认为 0。
+    pub priority: i64,
+    /// 规则编译后的匹配器。
+    pub matcher: Matcher,
+}
+
+/// 规则文件中某一行的错误。
+#[derive(Debug, PartialEq)]
+pub struct LineError {
+    /// 行号，从 1 开始。
+    pub line: usize,
+    /// 错误。
+    pub error: Error,
+}
+
+/// 规则集。
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// 从规则文件内容中加载规则集。
+    ///
+    /// 每行一条规则，格式为 `标签: 规则`。空行和以 `#` 开头的注释行会被跳过。
+    /// 标签后可以用 `@` 指定优先级，例如 `ad@10: 规则`。
+    /// 任意一行存在错误都会导致加载失败，错误中包含全部出错的行。
+    ///
+    /// # 例子
+    /// ```
+    /// use matchingram::ruleset::RuleSet;
+    /// use matchingram::models::Message;
+    ///
+    /// let rule_set = RuleSet::from_lines(
+    ///     r#"
+    /// ## 广告
+    /// ad: (message.text all {"承接" "广告"})
+    /// gambling: (message.text any {"菠菜" "博彩"})
+    /// "#,
+    /// )?;
+    /// let message = Message {
+    ///     text: Some(format!("东南亚博彩招聘")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(Some("gambling"), rule_set.match_message(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn from_lines(input: &str) -> Result<Self> {
+        Self::from_lines_with_delimiter(input, DEFAULT_DELIMITER)
+    }
+
+    /// 使用指定的分隔符从规则文件内容中加载规则集。
+    ///
+    /// 参考 [`from_lines`](#method.from_lines)。
+    pub fn from_lines_with_delimiter(input: &str, delimiter: &str) -> Result<Self> {
+        let mut rules = vec![];
+        let mut errors = vec![];
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match parse_line(line, delimiter) {
+                Ok(rule) => rules.push(rule),
+                Err(error) => errors.push(LineError { line: i + 1, error }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Self { rules })
+        } else {
+            Err(Error::RuleSetLoadFailed { errors })
+        }
+    }
+
+    /// 匹配消息，返回优先级最高的匹配规则的标签。
+    ///
+    /// 优先级相同时返回最先加入的规则，与 [`match_highest_priority`](#method.match_highest_priority) 一致。
+    pub fn match_message(&self, message: &Message) -> Result<Option<&str>> {
+        Ok(self
+            .match_highest_priority(message)?
+            .map(|rule| rule.label.as_str()))
+    }
+
+    /// 匹配消息，返回全部匹配的规则的标签。
+    ///
+    /// 标签按优先级从高到低排序，优先级相同时保持规则的加入顺序。
+    ///
+    /// # 例子
+    /// ```
+    /// use matchingram::ruleset::RuleSet;
+    /// use matchingram::models::Message;
+    ///
+    /// let rule_set = RuleSet::from_lines(
+    ///     r#"
+    /// ad: (message.text all {"承接" "广告"})
+    /// gambling@10: (message.text any {"菠菜" "博彩"})
+    /// "#,
+    /// )?;
+    /// let message = Message {
+    ///     text: Some(format!("承接博彩广告")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(vec!["gambling", "ad"], rule_set.match_all(&message)?);
+    /// # Ok::<(), matchingram::Error>(())
+    /// ```
+    pub fn match_all(&self, message: &Message) -> Result<Vec<&str>> {
+        Ok(self
+            .match_rules(message)?
+            .into_iter()
+            .map(|rule| rule.label.as_str())
+            .collect())
+    }
+
+    /// 匹配消息，返回优先级最高的匹配规则。
+    ///
+    /// 优先级相同时返回最先加入的规则。
+    pub fn match_highest_priority(&self, message: &Message) -> Result<Option<&Rule>> {
+        let mut highest: Option<&Rule> = None;
+        for rule in &self.rules {
+            // 优先级不高于已匹配的规则，无需再匹配。
+            if highest.is_some_and(|highest| rule.priority <= highest.priority) {
+                continue;
+            }
+            if rule.matcher.match_message(message)? {
+                highest = Some(rule);
+            }
+        }
+
+        Ok(highest)
+    }
+
+    // 返回全部匹配的规则，按优先级从高到低稳定排序。
+    fn match_rules(&self, message: &Message) -> Result<Vec<&Rule>> {
+        let mut matched = vec![];
+        for rule in &self.rules {
+            if rule.matcher.match_message(message)? {
+                matched.push(rule);
+            }
+        }
+
+        matched.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+        Ok(matched)
+    }
+}
+
+/// 由放行规则集和拒绝规则集构成的策略。
+///
+/// 优先级：只有拒绝规则匹配时消息才会被拒绝，此时如果放行规则也匹配，放行规则将否决拒绝规则。
+/// 没有拒绝规则匹配时，放行规则不会被匹配。两个规则集内部都以优先级最高的匹配规则为准，优先级相同时以最先加入的规则为准。
+///
+/// # 例子
+/// ```
+/// use matchingram::models::Message;
+/// use matchingram::ruleset::{Decision, Policy, RuleSet};
+///
+/// let policy = Policy {
+///     allow: RuleSet::from_lines(r#"admin: (message.from.id eq 10086)"#)?,
+///     deny: RuleSet::from_lines(r#"gambling: (message.text any {"菠菜" "博彩"})"#)?,
+/// };
+/// let message = Message {
+///     text: Some(format!("东南亚博彩招聘")),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(Decision::Deny { label: "gambling" }, policy.decide(&message)?);
+/// # Ok::<(), matchingram::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Policy {
+    /// 放行规则集。
+    pub allow: RuleSet,
+    /// 拒绝规则集。
+    pub deny: RuleSet,
+}
+
+/// 策略的决定。参考 [`Policy`](struct.Policy.html)。
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decision<'a> {
+    /// 没有拒绝规则匹配。
+    Pass,
+    /// 拒绝规则匹配，且没有放行规则匹配。
+    Deny {
+        /// 匹配的拒绝规则的标签。
+        label: &'a str,
+    },
+    /// 拒绝规则匹配，但被放行规则否决。
+    Allow {
+        /// 匹配的放行规则的标签。
+        label: &'a str,
+        /// 被否决的拒绝规则的标签。
+        vetoed: &'a str,
+    },
+}
+
+impl Policy {
+    /// 根据消息做出决定。
+    pub fn decide(&self, message: &Message) -> Result<Decision<'_>> {
+        let vetoed = match self.deny.match_message(message)? {
+            Some(label) => label,
+            None => return Ok(Decision::Pass),
+        };
+
+        match self.allow.match_message(message)? {
+            Some(label) => Ok(Decision::Allow { label, vetoed }),
+            None => Ok(Decision::Deny { label: vetoed }),
+        }
+    }
+}
+
+fn parse_line(line: &str, delimiter: &str) -> Result<Rule> {
+    let (label, rule) = line
+        .split_once(delimiter)
+        .ok_or_else(|| Error::MissingRuleDelimiter {
+            delimiter: delimiter.to_owned(),
+        })?;
+
+    let (label, priority) = match label.split_once(PRIORITY_DELIMITER) {
+        Some((label, priority)) => {
+            let priority = priority.trim();
+            let priority = priority
+                .parse::<i64>()
+                .map_err(|_| Error::InvalidRulePriority {
+                    priority: priority.to_owned(),
+                })?;
+
+            (label, priority)
+        }
+        None => (label, 0),
+    };
+
+    Ok(Rule {
+        label: label.trim().to_owned(),
+        priority,
+        matcher: Matcher::from_rule(rule.trim())?,
+    })
+}
@@ -0,0 +1,437 @@
+//! 文本特征的计算。
+//!
+//! 这里的函数为派生字段（例如 `message.text.max_run`）提供数据，它们不属于 Telegram 的消息结构。
+
+/// 不可见字符集合。
+///
+/// 这些字符在客户端中不显示或显示为空白，常被用于分割关键字或伪装空消息。
+pub const INVISIBLE_CHARS: &[char] = &[
+    '\u{00AD}', // 软连字符
+    '\u{034F}', // 组合用字形连接符
+    '\u{115F}', // 谚文初声填充符
+    '\u{1160}', // 谚文中声填充符
+    '\u{180E}', // 蒙古文元音分隔符
+    '\u{200B}', // 零宽空格
+    '\u{200C}', // 零宽非连接符
+    '\u{200D}', // 零宽连接符
+    '\u{2060}', // 词连接符
+    '\u{2061}', // 不可见函数应用
+    '\u{2062}', // 不可见乘号
+    '\u{2063}', // 不可见分隔符
+    '\u{2064}', // 不可见加号
+    '\u{2800}', // 盲文空白
+    '\u{3164}', // 谚文填充符
+    '\u{FEFF}', // 零宽不换行空格
+    '\u{FFA0}', // 半角谚文填充符
+];
+
+/// 计算同一字符连续重复出现的最长次数。以 Unicode 标量值为单位。
+pub fn max_run(text: &str) -> usize {
+    let mut max = 0;
+    let mut run = 0;
+    let mut last = None;
+
+    for c in text.chars() {
+        if last == Some(c) {
+            run += 1;
+        } else {
+            run = 1;
+            last = Some(c);
+        }
+        if run > max {
+            max = run;
+        }
+    }
+
+    max
+}
+
+/// 删除文本中出现在 `chars` 中的字符。
+pub fn strip_chars(text: &str, chars: &[char]) -> String {
+    text.chars().filter(|c| !chars.contains(c)).collect()
+}
+
+/// 将连续的空白字符折叠为单个空格。
+pub fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut is_last_white_space = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !is_last_white_space {
+                collapsed.push(' ');
+            }
+            is_last_white_space = true;
+        } else {
+            collapsed.push(c);
+            is_last_white_space = false;
+        }
+    }
+
+    collapsed
+}
+
+/// 计算大写字母在有大小写之分的字符中所占的百分比（0 到 100，向下取整）。没有大小写之分的字符时返回 0。
+pub fn upper_ratio(text: &str) -> usize {
+    let mut cased: usize = 0;
+    let mut upper: usize = 0;
+    for c in text.chars() {
+        if c.is_uppercase() {
+            upper += 1;
+            cased += 1;
+        } else if c.is_lowercase() {
+            cased += 1;
+        }
+    }
+
+    (upper * 100).checked_div(cased).unwrap_or(0)
+}
+
+/// 货币符号集合。
+pub const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥', '₿', '₽', '₹', '₮'];
+
+/// 货币代码和单位集合，匹配时不区分大小写。
+pub const CURRENCY_CODES: &[&str] = &[
+    "usd", "usdt", "eur", "gbp", "cny", "rmb", "jpy", "rub", "btc", "eth", "元", "美元", "美金",
+    "块",
+];
+
+/// 提取文本中与货币符号或货币代码相邻的金额。
+///
+/// 检测规则等价于以下不区分大小写的正则表达式，其中 `SYMBOL` 表示 [`CURRENCY_SYMBOLS`](constant.CURRENCY_SYMBOLS.html)
+/// 中的任意符号，`CODE` 表示 [`CURRENCY_CODES`](constant.CURRENCY_CODES.html) 中的任意代码：
+///
+/// ```text
+/// SYMBOL\s?(AMOUNT)|(AMOUNT)\s?(SYMBOL|CODE)
+/// AMOUNT = \d+(,\d{3})*(\.\d+)?
+/// ```
+///
+/// 由字母组成的货币代码之后不能紧跟字母，例如 `5 usdollar` 不会被识别。金额中的 `,` 是千位分隔符。
+pub fn amounts(text: &str) -> Vec<f64> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let is_digit = |pos: usize| chars.get(pos).is_some_and(char::is_ascii_digit);
+
+    let mut amounts = vec![];
+    let mut pos = 0;
+    while pos < chars.len() {
+        if !is_digit(pos) {
+            pos += 1;
+            continue;
+        }
+
+        let begin = pos;
+        while is_digit(pos) {
+            pos += 1;
+        }
+        // 千位分隔符之后必须恰好是三位数字。
+        while chars.get(pos) == Some(&',')
+            && (1..=3).all(|n| is_digit(pos + n))
+            && !is_digit(pos + 4)
+        {
+            pos += 4;
+        }
+        if chars.get(pos) == Some(&'.') && is_digit(pos + 1) {
+            pos += 1;
+            while is_digit(pos) {
+                pos += 1;
+            }
+        }
+
+        if has_currency_before(&chars, begin) || has_currency_after(&chars, pos) {
+            let amount = chars[begin..pos]
+                .iter()
+                .filter(|c| **c != ',')
+                .collect::<String>();
+            if let Ok(amount) = amount.parse() {
+                amounts.push(amount);
+            }
+        }
+    }
+
+    amounts
+}
+
+/// 文本中与货币相邻的最大金额。参考 [`amounts`](fn.amounts.html)。
+pub fn max_amount(text: &str) -> Option<f64> {
+    amounts(text)
+        .into_iter()
+        .fold(None, |max, amount| match max {
+            Some(max) if max >= amount => Some(max),
+            _ => Some(amount),
+        })
+}
+
+// 金额之前（允许间隔一个空白）是否是货币符号。
+fn has_currency_before(chars: &[char], begin: usize) -> bool {
+    let begin = match begin.checked_sub(1).map(|pos| chars[pos]) {
+        Some(c) if c.is_whitespace() => begin - 1,
+        _ => begin,
+    };
+
+    begin
+        .checked_sub(1)
+        .is_some_and(|pos| CURRENCY_SYMBOLS.contains(&chars[pos]))
+}
+
+// 金额之后（允许间隔一个空白）是否是货币符号或货币代码。
+fn has_currency_after(chars: &[char], end: usize) -> bool {
+    let end = match chars.get(end) {
+        Some(c) if c.is_whitespace() => end + 1,
+        _ => end,
+    };
+    if chars.get(end).is_some_and(|c| CURRENCY_SYMBOLS.contains(c)) {
+        return true;
+    }
+
+    CURRENCY_CODES.iter().any(|code| {
+        let code = code.chars().collect::<Vec<_>>();
+        let rest = &chars[end.min(chars.len())..];
+
+        rest.len() >= code.len()
+            && rest
+                .iter()
+                .zip(&code)
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+            && !(code[0].is_ascii_alphabetic()
+                && rest.get(code.len()).is_some_and(|c| c.is_alphabetic()))
+    })
+}
+
+/// 将 `\r\n` 和 `\r` 换行符统一替换为 `\n`。
+pub fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// 文本中是否包含不可见字符。
+pub fn has_invisible(text: &str) -> bool {
+    text.chars().any(|c| INVISIBLE_CHARS.contains(&c))
+}
+
+/// Unicode 双向控制字符集合。
+///
+/// 这些字符会改变文字的显示方向，常被用于伪装文件名（例如将 `exe` 后缀显示为 `pdf`）。
+pub const BIDI_CONTROL_CHARS: &[char] = &[
+    '\u{061C}', // 阿拉伯字母标记
+    '\u{200E}', // 从左至右标记
+    '\u{200F}', // 从右至左标记
+    '\u{202A}', // 从左至右嵌入
+    '\u{202B}', // 从右至左嵌入
+    '\u{202C}', // 弹出方向格式
+    '\u{202D}', // 从左至右覆盖
+    '\u{202E}', // 从右至左覆盖
+    '\u{2066}', // 从左至右隔离
+    '\u{2067}', // 从右至左隔离
+    '\u{2068}', // 第一强隔离
+    '\u{2069}', // 弹出方向隔离
+];
+
+/// 文本中是否包含双向控制字符。
+pub fn has_bidi_control(text: &str) -> bool {
+    text.chars().any(|c| BIDI_CONTROL_CHARS.contains(&c))
+}
+
+/// 文本是否为空白，即为空或只包含空白字符、不可见字符和双向控制字符。
+pub fn is_blank(text: &str) -> bool {
+    text.chars().all(|c| {
+        c.is_whitespace() || INVISIBLE_CHARS.contains(&c) || BIDI_CONTROL_CHARS.contains(&c)
+    })
+}
+
+/// 删除 emoji 中的变体选择符（`U+FE0E`、`U+FE0F`）和肤色修饰符（`U+1F3FB` 至 `U+1F3FF`）。
+pub fn strip_emoji_modifiers(text: &str) -> String {
+    text.chars()
+        .filter(|c| !matches!(c, '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}'))
+        .collect()
+}
+
+/// 同形字符映射表，将外形与拉丁字母相同的西里尔字母和希腊字母映射为对应的拉丁字母。
+///
+/// 数据来源于 Unicode 技术标准 #39 的 [confusables.txt](https://www.unicode.org/Public/security/latest/confusables.txt)，
+/// 仅保留了外形几乎无法分辨的字母。
+#[cfg(feature = "homoglyph")]
+pub const HOMOGLYPHS: &[(char, char)] = &[
+    // 西里尔字母
+    ('а', 'a'),
+    ('е', 'e'),
+    ('о', 'o'),
+    ('р', 'p'),
+    ('с', 'c'),
+    ('у', 'y'),
+    ('х', 'x'),
+    ('ѕ', 's'),
+    ('і', 'i'),
+    ('ј', 'j'),
+    ('ԁ', 'd'),
+    ('һ', 'h'),
+    ('ԛ', 'q'),
+    ('ԝ', 'w'),
+    ('А', 'A'),
+    ('В', 'B'),
+    ('Е', 'E'),
+    ('К', 'K'),
+    ('М', 'M'),
+    ('Н', 'H'),
+    ('О', 'O'),
+    ('Р', 'P'),
+    ('С', 'C'),
+    ('Т', 'T'),
+    ('Х', 'X'),
+    ('Ѕ', 'S'),
+    ('І', 'I'),
+    ('Ј', 'J'),
+    ('Ү', 'Y'),
+    ('Ԛ', 'Q'),
+    ('Ԝ', 'W'),
+    // 希腊字母
+    ('α', 'a'),
+    ('ο', 'o'),
+    ('ν', 'v'),
+    ('ρ', 'p'),
+    ('ι', 'i'),
+    ('Α', 'A'),
+    ('Β', 'B'),
+    ('Ε', 'E'),
+    ('Ζ', 'Z'),
+    ('Η', 'H'),
+    ('Ι', 'I'),
+    ('Κ', 'K'),
+    ('Μ', 'M'),
+    ('Ν', 'N'),
+    ('Ο', 'O'),
+    ('Ρ', 'P'),
+    ('Τ', 'T'),
+    ('Υ', 'Y'),
+    ('Χ', 'X'),
+];
+
+/// 将文本中的同形字符替换为对应的拉丁字母。参考 [`HOMOGLYPHS`](constant.HOMOGLYPHS.html)。
+#[cfg(feature = "homoglyph")]
+pub fn fold_homoglyphs(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            HOMOGLYPHS
+                .iter()
+                .find(|(homoglyph, _)| *homoglyph == c)
+                .map_or(c, |(_, latin)| *latin)
+        })
+        .collect()
+}
+
+// 解码的片段的最小长度，过短的片段容易误判。
+const MIN_ENCODED_LEN: usize = 8;
+
+/// 解码文本中以空白分隔的 base64 或百分号编码的片段，返回解码成功的结果。
+///
+/// 仅保留解码后为合法 UTF-8 且不包含控制字符的结果。base64 片段的长度至少为 8，同时支持标准和 URL 安全的字母表。
+pub fn decode_segments(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|segment| segment.chars().count() >= MIN_ENCODED_LEN)
+        .filter_map(|segment| {
+            if segment.contains('%') {
+                decode_percent(segment)
+            } else {
+                decode_base64(segment).and_then(|bytes| String::from_utf8(bytes).ok())
+            }
+        })
+        .filter(|decoded| {
+            !decoded
+                .chars()
+                .any(|c| c.is_control() && !c.is_whitespace())
+        })
+        .collect()
+}
+
+/// 解码 base64 编码的数据，末尾的 `=` 可以省略。
+pub fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=');
+    if data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+/// 解码百分号编码的数据，至少需要包含一个编码的字节。
+pub fn decode_percent(data: &str) -> Option<String> {
+    let input = data.as_bytes();
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut encoded = false;
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'%' {
+            let hex = std::str::from_utf8(input.get(i + 1..i + 3)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            encoded = true;
+            i += 3;
+        } else {
+            bytes.push(input[i]);
+            i += 1;
+        }
+    }
+
+    if encoded {
+        String::from_utf8(bytes).ok()
+    } else {
+        None
+    }
+}
+
+/// 常见的 leetspeak 字符映射表。
+///
+/// `1` 既可能表示 `i` 也可能表示 `l`，因此不在此表中，参考 [`fold_leetspeak`](fn.fold_leetspeak.html)。
+#[cfg(feature = "leetspeak")]
+pub const LEETSPEAK: &[(char, char)] = &[
+    ('4', 'a'),
+    ('@', 'a'),
+    ('8', 'b'),
+    ('3', 'e'),
+    ('6', 'g'),
+    ('0', 'o'),
+    ('$', 's'),
+    ('5', 's'),
+    ('7', 't'),
+];
+
+/// 将文本转换为小写，并将 leetspeak 字符替换为对应的字母。
+///
+/// 由于 `1` 既可能表示 `i` 也可能表示 `l`，包含 `1` 时会返回两个结果：分别将 `1` 替换为 `i` 和 `l`。
+#[cfg(feature = "leetspeak")]
+pub fn fold_leetspeak(text: &str) -> Vec<String> {
+    let folded = text
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            LEETSPEAK
+                .iter()
+                .find(|(leet, _)| *leet == c)
+                .map_or(c, |(_, letter)| *letter)
+        })
+        .collect::<String>();
+
+    if folded.contains('1') {
+        vec![folded.replace('1', "i"), folded.replace('1', "l")]
+    } else {
+        vec![folded]
+    }
+}
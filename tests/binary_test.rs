@@ -0,0 +1,46 @@
+#![cfg(feature = "binary")]
+
+use matchingram::compile_rule;
+use matchingram::matches::Matcher;
+use matchingram::models::Message;
+
+#[test]
+fn test_binary_round_trip() {
+    let mut matcher = compile_rule(
+        r#"(message.text any {"博彩" "菠菜"} and message.message_id in {1 2 3}) or (message.text.len gt 100)"#,
+    )
+    .unwrap();
+    matcher.factor_common_prefix();
+    let bytes = matcher.to_bytes().unwrap();
+    let loaded_matcher = Matcher::from_bytes(&bytes).unwrap();
+
+    assert_eq!(matcher.groups, loaded_matcher.groups);
+    assert_eq!(matcher.common_prefix, loaded_matcher.common_prefix);
+
+    let message = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        message_id: 2,
+        ..Default::default()
+    };
+    assert_eq!(
+        matcher.match_message(&message).unwrap(),
+        loaded_matcher.match_message(&message).unwrap()
+    );
+    assert!(loaded_matcher.match_message(&message).unwrap());
+
+    assert!(Matcher::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+}
+
+#[test]
+fn test_binary_round_trip_patterns() {
+    let matcher = compile_rule(r#"(message.text all_matches {"\d{4}" "(?i)usdt"})"#).unwrap();
+    let loaded_matcher = Matcher::from_bytes(&matcher.to_bytes().unwrap()).unwrap();
+
+    assert_eq!(matcher.groups, loaded_matcher.groups);
+
+    let message = Message {
+        text: Some(String::from("转账 5000 USDT")),
+        ..Default::default()
+    };
+    assert!(loaded_matcher.match_message(&message).unwrap());
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "cache")]
+
+use matchingram::cache::CachedMatcher;
+use matchingram::compile_rule;
+use matchingram::matches::MessageHistory;
+use matchingram::models::{Message, User};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_cached_matcher() {
+    let matcher = compile_rule(r#"(message.text any {"博彩"})"#).unwrap();
+    let mut cached_matcher = CachedMatcher::new(matcher, 2);
+
+    let message1 = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        ..Default::default()
+    };
+    let message2 = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+
+    assert!(cached_matcher.match_message(&message1).unwrap());
+    assert_eq!(0, cached_matcher.hits());
+    assert!(cached_matcher.match_message(&message1).unwrap());
+    assert_eq!(1, cached_matcher.hits());
+
+    assert!(!cached_matcher.match_message(&message2).unwrap());
+    assert_eq!(1, cached_matcher.hits());
+    assert!(!cached_matcher.match_message(&message2).unwrap());
+    assert_eq!(2, cached_matcher.hits());
+
+    cached_matcher.clear();
+    assert!(cached_matcher.match_message(&message1).unwrap());
+    assert_eq!(2, cached_matcher.hits());
+}
+
+#[test]
+fn test_cached_matcher_with_message_history() {
+    // 除第一次外，每次查询都视作重复。
+    #[derive(Default)]
+    struct History(AtomicUsize);
+
+    impl MessageHistory for History {
+        fn is_repeat(&self, _sender_id: i64, _text: &str) -> bool {
+            self.0.fetch_add(1, Ordering::SeqCst) > 0
+        }
+    }
+
+    let mut matcher = compile_rule(r#"(message.is_repeat)"#).unwrap();
+    matcher.options.message_history = Some(Arc::new(History::default()));
+    let mut cached_matcher = CachedMatcher::new(matcher, 2);
+    let message = Message {
+        from: Some(User {
+            id: 10086,
+            is_bot: false,
+            first_name: String::from("Spammer"),
+            last_name: None,
+            username: None,
+            language_code: None,
+        }),
+        text: Some(String::from("加群领红包")),
+        ..Default::default()
+    };
+
+    assert!(!cached_matcher.match_message(&message).unwrap());
+    assert!(cached_matcher.match_message(&message).unwrap());
+    assert_eq!(0, cached_matcher.hits());
+}
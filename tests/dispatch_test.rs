@@ -0,0 +1,156 @@
+#![cfg(feature = "json")]
+
+use matchingram::matches::{Cont, Field, Value};
+use matchingram::models::Message;
+use matchingram::operator::Operator;
+use matchingram::Error;
+use strum::IntoEnumIterator;
+
+// 所有字段都存在的消息。
+const FULL_MESSAGE_JSON: &str = r#"{
+    "message_id": 2,
+    "message_thread_id": 1,
+    "from": {"id": 1, "is_bot": false, "first_name": "x", "last_name": "x", "username": "x", "language_code": "x"},
+    "date": 100,
+    "chat": {"id": 1, "type": "supergroup", "title": "x"},
+    "forward_from_chat": {"id": 1, "type": "channel", "title": "x"},
+    "forward_date": 1,
+    "is_topic_message": true,
+    "reply_to_message": {
+        "message_id": 1,
+        "from": {"id": 1, "is_bot": false, "first_name": "x", "username": "x"}
+    },
+    "text": "/x",
+    "entities": [{"type": "bot_command", "offset": 0, "length": 2}],
+    "animation": {"duration": 1, "file_name": "x", "mime_type": "x", "file_size": 1},
+    "audio": {"duration": 1, "performer": "x", "title": "x", "mime_type": "x", "file_size": 1},
+    "document": {"file_name": "x", "mime_type": "x", "file_size": 1},
+    "photo": [{"width": 1, "height": 1, "file_size": 1}],
+    "sticker": {"is_animated": true, "emoji": "x", "set_name": "x"},
+    "video": {"duration": 1, "mime_type": "x", "file_size": 1},
+    "voice": {"duration": 1, "mime_type": "x", "file_size": 1},
+    "caption": "x",
+    "dice": {"emoji": "🎲", "value": 1},
+    "poll": {"id": "1", "question": "x", "type": "regular"},
+    "venue": {"location": {"longitude": 1.0, "latitude": 1.0}, "title": "x", "address": "x"},
+    "location": {"longitude": 1.0, "latitude": 1.0},
+    "new_chat_members": [{"id": 1, "is_bot": false, "first_name": "x", "username": "x"}],
+    "left_chat_member": {"id": 1, "is_bot": false, "first_name": "x"},
+    "new_chat_title": "x",
+    "new_chat_photo": [{"width": 1, "height": 1}],
+    "pinned_message": {"message_id": 1}
+}"#;
+
+fn is_dispatch_error(error: &Error) -> bool {
+    matches!(error, Error::UnsupportedOperator { .. })
+}
+
+#[test]
+fn test_every_registered_operator_dispatches() {
+    let message: Message = serde_json::from_str(FULL_MESSAGE_JSON).unwrap();
+    let values = [
+        Value::Integer(1),
+        Value::Decimal(1.0),
+        Value::from_str("🎲"),
+        Value::from_str("x"),
+    ];
+
+    for field in Field::iter() {
+        // 不带运算符的字段同样需要有对应的匹配实现。
+        let result = Cont::with_field(false, field)
+            .unwrap()
+            .match_message(&message);
+        if let Err(e) = &result {
+            assert!(
+                !is_dispatch_error(e),
+                "`{}` is registered but not dispatched: {}",
+                field.to_string(),
+                e
+            );
+        }
+
+        for operator in Operator::iter() {
+            let mut results = vec![];
+            for value in &values {
+                match Cont::with_operator(false, field, operator, vec![value.clone()]) {
+                    Ok(cont) => results.push(cont.match_message(&message)),
+                    Err(Error::UnsupportedFieldOperatorSuggestion { .. }) => break,
+                    // 值不合法（例如骰子的 emoji）。
+                    Err(_) => continue,
+                }
+            }
+            if results.is_empty() {
+                continue;
+            }
+            for result in &results {
+                if let Err(e) = result {
+                    assert!(
+                        !is_dispatch_error(e),
+                        "`{} {}` is registered but not dispatched: {}",
+                        field.to_string(),
+                        operator.to_string(),
+                        e
+                    );
+                }
+            }
+            // 至少有一种类型的值可以完成匹配。
+            assert!(
+                results.iter().any(|r| r.is_ok()),
+                "`{} {}` failed with every value: {:?}",
+                field.to_string(),
+                operator.to_string(),
+                results
+            );
+        }
+    }
+}
+
+#[test]
+fn test_every_field_without_operator_dispatches() {
+    let message: Message = serde_json::from_str(FULL_MESSAGE_JSON).unwrap();
+
+    for field in Field::iter() {
+        let cont = Cont::with_field(false, field).unwrap();
+        if let Err(e) = cont.match_message(&message) {
+            assert!(
+                !is_dispatch_error(&e),
+                "`{}` is not dispatched: {}",
+                field.to_string(),
+                e
+            );
+        }
+    }
+}
+
+fn is_supported(field: Field, operator: Operator, value: Value) -> bool {
+    !matches!(
+        Cont::with_operator(false, field, operator, vec![value]),
+        Err(Error::UnsupportedFieldOperatorSuggestion { .. })
+    )
+}
+
+#[test]
+fn test_every_hd_field_supports_td() {
+    for field in Field::iter() {
+        if is_supported(field, Operator::Hd, Value::from_str("x")) {
+            assert!(
+                is_supported(field, Operator::Td, Value::from_str("x")),
+                "`{}` supports `hd` but not `td`",
+                field.to_string()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_every_gt_field_supports_lt() {
+    for field in Field::iter() {
+        if is_supported(field, Operator::Gt, Value::Integer(1)) {
+            assert!(
+                is_supported(field, Operator::Lt, Value::Integer(1)),
+                "`{}` supports `gt` but not `lt`",
+                field.to_string()
+            );
+        }
+    }
+}
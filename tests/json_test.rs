@@ -0,0 +1,93 @@
+#![cfg(feature = "json")]
+
+use matchingram::compile_rule;
+use matchingram::matches::Matcher;
+use matchingram::models::{Message, User};
+
+#[test]
+fn test_json_round_trip() {
+    let rules = [
+        r#"(message.text any {"博彩" "菠菜"} and message.message_id in {1 2 3}) or (message.text.len gt 100)"#,
+        r#"(not message.from.is_bot and message.text.max_amount ge 5000.0)"#,
+        r#"(message.text all_matches {"\d{4}" "(?i)usdt"})"#,
+        r#"(message.text.len gt field:message.caption.len)"#,
+    ];
+    let messages = [
+        Message {
+            text: Some(String::from("东南亚博彩招聘")),
+            message_id: 2,
+            ..Default::default()
+        },
+        Message {
+            text: Some(String::from("earn 5000 USDT daily")),
+            caption: Some(String::from("hi")),
+            ..Default::default()
+        },
+        Message {
+            text: Some(String::from("你好")),
+            ..Default::default()
+        },
+    ];
+
+    for rule in &rules {
+        let mut matcher = compile_rule(*rule).unwrap();
+        matcher.factor_common_prefix();
+        let json = matcher.to_json().unwrap();
+        let loaded_matcher = Matcher::from_json(&json).unwrap();
+
+        assert_eq!(matcher.groups, loaded_matcher.groups);
+        assert_eq!(matcher.common_prefix, loaded_matcher.common_prefix);
+        for message in &messages {
+            assert_eq!(
+                matcher.match_message(message).unwrap(),
+                loaded_matcher.match_message(message).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_json_unknown_field() {
+    let matcher = compile_rule(r#"(message.text any {"博彩"})"#).unwrap();
+    let json = matcher
+        .to_json()
+        .unwrap()
+        .replace("message.text", "message.txt");
+    let r = Matcher::from_json(&json);
+
+    assert!(r.is_err());
+    assert!(r
+        .unwrap_err()
+        .to_string()
+        .starts_with(r#"unknown `"message.txt"` field"#));
+
+    let json = matcher.to_json().unwrap().replace(r#""any""#, r#""some""#);
+    assert!(Matcher::from_json(&json)
+        .unwrap_err()
+        .to_string()
+        .starts_with(r#"unknown `"some"` operator"#));
+}
+
+#[test]
+fn test_json_skips_value_set() {
+    let matcher = compile_rule(r#"(message.from.id in {10010 10080..10090})"#).unwrap();
+    let json = matcher.to_json().unwrap();
+
+    assert!(!json.contains("value_set"));
+
+    let loaded_matcher = Matcher::from_json(&json).unwrap();
+    assert!(loaded_matcher.groups[0][0].value_set.is_some());
+
+    let message = Message {
+        from: Some(User {
+            id: 10086,
+            is_bot: false,
+            first_name: String::from("Spammer"),
+            last_name: None,
+            username: None,
+            language_code: None,
+        }),
+        ..Default::default()
+    };
+    assert!(loaded_matcher.match_message(&message).unwrap());
+}
@@ -80,7 +80,10 @@ fn test_lex_number() {
     let r = lexer.tokenize();
 
     assert!(r.is_err());
-    assert_eq!("failed to parse from column 23", r.unwrap_err().to_string());
+    assert_eq!(
+        "failed to parse from line 1, column 23",
+        r.unwrap_err().to_string()
+    );
 
     let rule = r#"(message.longitude gt 19201080.)"#;
     let input = rule.chars().collect::<Vec<_>>();
@@ -88,7 +91,10 @@ fn test_lex_number() {
     let r = lexer.tokenize();
 
     assert!(r.is_err());
-    assert_eq!("failed to parse from column 23", r.unwrap_err().to_string());
+    assert_eq!(
+        "failed to parse from line 1, column 23",
+        r.unwrap_err().to_string()
+    );
 
     let rule = r#"(message.longitude gt 1920.108.0)"#;
     let input = rule.chars().collect::<Vec<_>>();
@@ -96,5 +102,340 @@ fn test_lex_number() {
     let r = lexer.tokenize();
 
     assert!(r.is_err());
-    assert_eq!("failed to parse from column 23", r.unwrap_err().to_string());
+    assert_eq!(
+        "failed to parse from line 1, column 23",
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_token_display() {
+    assert_eq!("(", OpenParenthesis.to_string());
+    assert_eq!("}", CloseBrace.to_string());
+    assert_eq!("\"", Quote.to_string());
+    assert_eq!("field", Field.to_string());
+    assert_eq!("operator", Operator.to_string());
+    assert_eq!("and", And.to_string());
+    assert_eq!("end of input", EOF.to_string());
+
+    let err = matchingram::Error::InferPositionFailed { token: Field };
+    assert_eq!(
+        "failed to infer position from token `field`",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_lex_digit_separators() {
+    let rule = r#"(message.from.id eq 1_000_012 and message.from.id ge -1,000,012)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+
+    assert_eq!(
+        Some(&(Integer, "1_000_012".to_owned())),
+        lexer.token_data_owner().unwrap().get(3)
+    );
+
+    let rule = r#"(message.location.latitude gt 1_920.108)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+
+    assert_eq!(
+        Some(&(Decimal, "1_920.108".to_owned())),
+        lexer.token_data_owner().unwrap().get(3)
+    );
+
+    for (rule, column) in [
+        (r#"(message.from.id eq 1_000_)"#, 26),
+        (r#"(message.from.id eq 1__000)"#, 22),
+        (r#"(message.from.id eq 1,,000)"#, 22),
+        (r#"(message.from.id eq 1_,000)"#, 22),
+    ] {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        let r = lexer.tokenize();
+
+        assert!(r.is_err());
+        assert_eq!(
+            format!("invalid digit separator at line 1, column {}", column),
+            r.unwrap_err().to_string()
+        );
+    }
+
+    // 不能以分隔符开头。
+    let rule = r#"(message.from.id eq _1000)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    let r = lexer.tokenize();
+
+    assert!(r.is_err());
+    assert_eq!(
+        "failed to parse from line 1, column 21",
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_lex_comma_separated_values() {
+    let tokenize = |rule: &str| {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        lexer.tokenize().unwrap();
+
+        lexer.token_data_owner().unwrap()
+    };
+
+    let expected = tokenize(r#"(message.text any {"a" "b"})"#);
+    assert_eq!(expected, tokenize(r#"(message.text any {"a", "b"})"#));
+    assert_eq!(expected, tokenize(r#"(message.text any {"a","b",})"#));
+    assert_eq!(expected, tokenize(r#"(message.text any {"a" ,"b" , })"#));
+
+    let expected = tokenize(r#"(message.from.id in {1_000 2 -3})"#);
+    assert_eq!(expected, tokenize(r#"(message.from.id in {1_000, 2,-3})"#));
+    assert_eq!(expected, tokenize(r#"(message.from.id in {1_000,2 -3,})"#));
+}
+
+#[test]
+fn test_lex_underscore_operators() {
+    for operator in &[
+        "not_in",
+        "hd_any",
+        "any_count",
+        "all",
+        "any",
+        "and_x",
+        "or_x",
+    ] {
+        let rule = format!(
+            r#"(message.text {} {{"a"}} and message.animation)"#,
+            operator
+        );
+        let input = rule.chars().collect::<Vec<_>>();
+
+        let mut lexer = Lexer::new(&input);
+        lexer.tokenize().unwrap();
+
+        let truthy = [
+            (OpenParenthesis, String::from("(")),
+            (Field, String::from("message.text")),
+            (Operator, String::from(*operator)),
+            (OpenBrace, String::from("{")),
+            (Quote, String::from("\"")),
+            (Letter, String::from("a")),
+            (Quote, String::from("\"")),
+            (CloseBrace, String::from("}")),
+            (And, String::from("and")),
+            (Field, String::from("message.animation")),
+            (CloseParenthesis, String::from(")")),
+            (EOF, String::from("")),
+        ];
+
+        assert_eq!(truthy.len(), lexer.output().len(), "{}", operator);
+        for (i, mapping) in lexer.token_data_owner().unwrap().into_iter().enumerate() {
+            assert_eq!(truthy[i], mapping, "{}", operator);
+        }
+    }
+
+    // 后面紧跟 `and` 关键字的不是运算符。
+    let rule = r#"(message.animation and not message.text in {"a"})"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((And, String::from("and")), tokens[2]);
+    assert_eq!((Not, String::from("not")), tokens[3]);
+    assert_eq!((Operator, String::from("in")), tokens[5]);
+
+    // 规则在运算符处结束。
+    let rule = r#"(message.text not_in"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Operator, String::from("not_in")), tokens[2]);
+}
+
+#[test]
+fn test_lex_field_at_end() {
+    let rule = r#"(message.animation"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Field, String::from("message.animation")), tokens[1]);
+    assert_eq!((EOF, String::from("")), tokens[2]);
+}
+
+#[test]
+fn test_lex_escaped_quotes() {
+    let rule = r#"(message.text contains "a \"b\" c")"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Letter, String::from(r#"a \"b\" c"#)), tokens[4]);
+    assert_eq!((Quote, String::from("\"")), tokens[5]);
+    assert_eq!((CloseParenthesis, String::from(")")), tokens[6]);
+
+    // 结束引号前的转义反斜杠。
+    let rule = r#"(message.text eq "C:\\")"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Letter, String::from(r#"C:\\"#)), tokens[4]);
+    assert_eq!((Quote, String::from("\"")), tokens[5]);
+
+    // 转义的引号不能结束文字。
+    let rule = r#"(message.text eq "abc\")"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    assert!(lexer.tokenize().is_err());
+
+    // v1 不处理转义，反斜杠后的引号结束文字。
+    let rule = "#lang v1\n(message.text eq \"abc\\\")";
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Letter, String::from(r#"abc\"#)), tokens[4]);
+    assert_eq!((CloseParenthesis, String::from(")")), tokens[6]);
+}
+
+#[test]
+fn test_lex_negative_number() {
+    use matchingram::Error;
+
+    let rule = r#"(message.from.id gt -100001)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Integer, String::from("-100001")), tokens[3]);
+    // 位置包含符号。
+    assert_eq!(20, lexer.positions()[3].begin);
+    assert_eq!(27, lexer.positions()[3].end);
+
+    let rule = r#"(message.location.latitude in {-0.2 -1})"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    lexer.tokenize().unwrap();
+    let tokens = lexer.token_data_owner().unwrap();
+    assert_eq!((Decimal, String::from("-0.2")), tokens[4]);
+    assert_eq!((Integer, String::from("-1")), tokens[5]);
+
+    // 规则不支持算术运算。
+    for rule in &[
+        r#"(message.text gt 5-3)"#,
+        r#"(message.text gt - 3)"#,
+        r#"(message.text gt --3)"#,
+    ] {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        assert_eq!(
+            Err(Error::ParseFailed {
+                line: 1,
+                column: 18
+            }),
+            lexer.tokenize(),
+            "{}",
+            rule
+        );
+    }
+}
+
+#[test]
+fn test_lex_dialect() {
+    use matchingram::lexer::Dialect;
+    use matchingram::Error;
+
+    let tokenize = |rule: &str| {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        lexer.tokenize()?;
+
+        Ok::<_, Error>((lexer.dialect(), lexer.token_data_owner()?))
+    };
+
+    let (dialect, expected) = tokenize(r#"(message.text any {"a" "b"})"#).unwrap();
+    assert_eq!(Dialect::V2, dialect);
+
+    // v1 不允许逗号和注释。
+    let (dialect, tokens) = tokenize("#lang v1\n(message.text any {\"a\" \"b\"})").unwrap();
+    assert_eq!(Dialect::V1, dialect);
+    assert_eq!(expected, tokens);
+    assert_eq!(
+        Err(Error::ParseFailed {
+            line: 2,
+            column: 23
+        }),
+        tokenize("#lang v1\n(message.text any {\"a\", \"b\"})").map(|_| ())
+    );
+    assert_eq!(
+        Err(Error::ParseFailed {
+            line: 2,
+            column: 30
+        }),
+        tokenize("#lang v1\n(message.text any {\"a\" \"b\"}) # spam").map(|_| ())
+    );
+
+    // v2 允许逗号和注释。
+    let (dialect, tokens) = tokenize("#lang v2\n(message.text any {\"a\", \"b\"})").unwrap();
+    assert_eq!(Dialect::V2, dialect);
+    assert_eq!(expected, tokens);
+    let rule = "  #lang v2\n# 博彩广告\n(message.text any # 关键字\n  {\"a\", \"b\"}) # 结束";
+    assert_eq!(expected, tokenize(rule).unwrap().1);
+
+    assert_eq!(
+        Err(Error::UnknownDialect {
+            name: String::from("v3"),
+            line: 1,
+            column: 7
+        }),
+        tokenize("#lang v3\n(message.text any {\"a\" \"b\"})").map(|_| ())
+    );
+    assert_eq!(
+        Err(Error::UnknownDialect {
+            name: String::new(),
+            line: 1,
+            column: 6
+        }),
+        tokenize("#lang\n(message.text any {\"a\" \"b\"})").map(|_| ())
+    );
+
+    // 以 `#lang` 开头的普通注释不是指令。
+    let (dialect, tokens) = tokenize("#language: 中文\n(message.text any {\"a\", \"b\"})").unwrap();
+    assert_eq!(Dialect::V2, dialect);
+    assert_eq!(expected, tokens);
+}
+
+#[test]
+fn test_lex_range() {
+    let tokenize = |rule: &str| {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        lexer.tokenize().unwrap();
+
+        lexer.token_data_owner().unwrap()
+    };
+
+    let tokens = tokenize(r#"(message.from.id in {1..100 500 -10..-1, 1_000..2_000})"#);
+    assert_eq!((Range, String::from("1..100")), tokens[4]);
+    assert_eq!((Integer, String::from("500")), tokens[5]);
+    assert_eq!((Range, String::from("-10..-1")), tokens[6]);
+    assert_eq!((Range, String::from("1_000..2_000")), tokens[7]);
+
+    // 不完整的范围不是合法的数字。
+    for rule in &[
+        r#"(message.from.id in {1..})"#,
+        r#"(message.from.id in {..5})"#,
+        r#"(message.from.id in {1...5})"#,
+        r#"(message.from.id in {1..5.0})"#,
+    ] {
+        let input = rule.chars().collect::<Vec<_>>();
+        let mut lexer = Lexer::new(&input);
+        assert!(lexer.tokenize().is_err());
+    }
 }
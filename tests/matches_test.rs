@@ -1,3 +1,5 @@
+#![cfg(feature = "json")]
+
 use matchingram::rule_match_json;
 
 #[test]
@@ -37,6 +39,12 @@ fn test_matcher() {
     let rule = r#"(message.text.len le 4)"#;
     assert!(!rule_match_json(rule, json_data).unwrap());
 
+    let rule = r#"(message.text.len lt 6)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.text.len lt 5)"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
     let rule = r#"(message.from.first_name in {"Java" "Rust"})"#;
     assert!(rule_match_json(rule, json_data).unwrap());
 
@@ -46,3 +54,2124 @@ fn test_matcher() {
     let rule = r#"(message.from.first_name hd "Rus")"#;
     assert!(rule_match_json(rule, json_data).unwrap());
 }
+
+#[test]
+fn test_require_all_fields() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let message = Message {
+        text: Some(String::from("Hello")),
+        ..Default::default()
+    };
+
+    let rule = r#"(not message.from.first_name eq "Spam" and message.text eq "Hello")"#;
+    let mut matcher = compile_rule(rule).unwrap();
+    // 宽松模式下不存在的字段被视作条件不成立，取反后成立。
+    assert!(matcher.match_message(&message).unwrap());
+
+    matcher.options.require_all_fields = true;
+    // 严格模式下字段不存在会令整个组匹配失败。
+    assert!(!matcher.match_message(&message).unwrap());
+
+    let rule = r#"(message.from.first_name eq "Spam") or (message.text eq "Hello")"#;
+    let mut matcher = compile_rule(rule).unwrap();
+    matcher.options.require_all_fields = true;
+    // 仅影响引用了不存在字段的组。
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_unsupported_operator_suggestion() {
+    use matchingram::compile_rule;
+
+    let r = compile_rule(r#"(message.from.id any {"x"})"#);
+
+    assert!(r.is_err());
+    assert_eq!(
+        "the field `message.from.id` does not support the `any` operator, supported operators: eq, in, not_in, gt, lt, ge, le, between",
+        r.unwrap_err().to_string()
+    );
+
+    let r = compile_rule(r#"(message.from.is_bot eq 1)"#);
+    assert_eq!(
+        "the field `message.from.is_bot` does not support the `eq` operator, supported operators: none",
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_matcher_from_table() {
+    use matchingram::matches::{Field, Matcher, RowRelation};
+    use matchingram::models::Message;
+    use matchingram::operator::Operator;
+
+    let rows = [
+        (
+            Field::MessageText,
+            Operator::Any,
+            vec![format!("博彩"), format!("菠菜")],
+        ),
+        (Field::MessageText, Operator::All, vec![format!("招聘")]),
+        (
+            Field::MessageText,
+            Operator::Eq,
+            vec![format!("东南亚博彩招聘")],
+        ),
+    ];
+    let message1 = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        ..Default::default()
+    };
+    let message2 = Message {
+        text: Some(String::from("菠菜需要的来")),
+        ..Default::default()
+    };
+
+    let matcher = Matcher::from_table(&rows, RowRelation::And).unwrap();
+    assert_eq!(1, matcher.groups.len());
+    assert_eq!(3, matcher.groups[0].len());
+    assert!(matcher.match_message(&message1).unwrap());
+    assert!(!matcher.match_message(&message2).unwrap());
+
+    let matcher = Matcher::from_table(&rows, RowRelation::Or).unwrap();
+    assert_eq!(3, matcher.groups.len());
+    assert!(matcher.match_message(&message1).unwrap());
+    assert!(matcher.match_message(&message2).unwrap());
+
+    let rows = [(Field::MessageText, Operator::Gt, vec![format!("1")])];
+    assert!(Matcher::from_table(&rows, RowRelation::And).is_err());
+}
+
+#[test]
+fn test_text_max_run() {
+    let json_data = r#"{"text": "好好好aaaaaaaaaaaa!!"}"#;
+
+    let rule = r#"(message.text.max_run ge 10)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.text.max_run eq 12)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "Hello, world!"}"#;
+
+    let rule = r#"(message.text.max_run ge 10)"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.text.max_run eq 2)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_dice_emoji_validation() {
+    use matchingram::compile_rule;
+
+    let json_data = r#"{"dice": {"emoji": "🎯"}}"#;
+
+    let rule = r#"(message.dice.emoji eq "🎯")"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.dice.emoji in {"🎲" "🎰"})"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let r = compile_rule(r#"(message.dice.emoji in {"🎲" "🎱"})"#);
+    assert!(r.is_err());
+    assert_eq!(
+        r#"the value `"🎱"` of the field `"message.dice.emoji"` is invalid"#,
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_matcher_score() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let rule = r#"(message.text any {"博彩" "菠菜"}) or (message.text any {"招聘" "需要"})"#;
+    let mut matcher = compile_rule(rule).unwrap();
+    matcher.weights = vec![3.0, 1.5];
+
+    let message1 = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        ..Default::default()
+    };
+    let message2 = Message {
+        text: Some(String::from("柬埔寨需要人")),
+        ..Default::default()
+    };
+    let message3 = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+
+    assert_eq!(4.5, matcher.score(&message1).unwrap());
+    assert_eq!(1.5, matcher.score(&message2).unwrap());
+    assert_eq!(0.0, matcher.score(&message3).unwrap());
+    assert!(matcher.match_message(&message2).unwrap());
+    assert!(!matcher.match_message(&message3).unwrap());
+
+    // 未设置的权重默认为 `1.0`。
+    matcher.weights = vec![];
+    assert_eq!(2.0, matcher.score(&message1).unwrap());
+
+    // 匹配结果不考虑权重，权重为 `0` 的组匹配成功时得分不大于 `0`。
+    matcher.weights = vec![3.0, 0.0];
+    assert!(matcher.match_message(&message2).unwrap());
+    assert_eq!(0.0, matcher.score(&message2).unwrap());
+}
+
+#[test]
+fn test_ignored_punctuation() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let message = Message {
+        text: Some(String::from("c.a.s.i.n.o, join now!")),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.text any {"casino"})"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+
+    matcher.options.ignored_punctuation = vec!['.', ',', '!'];
+    assert!(matcher.match_message(&message).unwrap());
+
+    // 不影响 `eq` 运算符。
+    let mut matcher = compile_rule(r#"(message.text eq "casino join now")"#).unwrap();
+    matcher.options.ignored_punctuation = vec!['.', ',', '!'];
+    assert!(!matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_topic_message() {
+    let json_data = r#"{"text": "Hello", "message_thread_id": 42, "is_topic_message": true}"#;
+
+    let rule = r#"(message.is_topic_message and message.message_thread_id eq 42)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.message_thread_id eq 43)"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "Hello"}"#;
+
+    let rule = r#"(message.is_topic_message)"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(not message.message_thread_id eq 42)"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_collapse_whitespace() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let message = Message {
+        text: Some(String::from("hello   world\n\nbye")),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.text eq "hello world bye")"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+    matcher.options.collapse_whitespace = true;
+    assert!(matcher.match_message(&message).unwrap());
+
+    let mut matcher = compile_rule(r#"(message.text all {"hello world" "world bye"})"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+    matcher.options.collapse_whitespace = true;
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_text_has_invisible() {
+    let rule = r#"(message.text.has_invisible)"#;
+
+    let json_data = r#"{"text": "ca\u200bsino"}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "casino"}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"caption": "ca\u200bsino"}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_text_is_blank() {
+    let rule = r#"(message.text.is_blank)"#;
+
+    // 没有文本时也成立。
+    let json_data = r#"{"caption": "casino"}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": ""}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "   "}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "\u200b\u200d\ufeff"}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": " \n\u3164 "}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": " \u200b. "}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+    assert!(rule_match_json(r#"(not message.text.is_blank)"#, json_data).unwrap());
+}
+
+#[test]
+fn test_language_code_prefix() {
+    let zh_user =
+        r#"{"from": {"id": 1, "is_bot": false, "first_name": "小明", "language_code": "zh-Hant"}}"#;
+    let en_user =
+        r#"{"from": {"id": 2, "is_bot": false, "first_name": "Jay", "language_code": "en-US"}}"#;
+
+    let rule = r#"(message.from.language_code hd "zh")"#;
+    assert!(rule_match_json(rule, zh_user).unwrap());
+    assert!(!rule_match_json(rule, en_user).unwrap());
+
+    let rule = r#"(message.from.language_code in {"zh-Hans" "zh-Hant"})"#;
+    assert!(rule_match_json(rule, zh_user).unwrap());
+    assert!(!rule_match_json(rule, en_user).unwrap());
+
+    let rule = r#"(not message.from.language_code hd "zh")"#;
+    assert!(!rule_match_json(rule, zh_user).unwrap());
+    assert!(rule_match_json(rule, en_user).unwrap());
+}
+
+#[test]
+fn test_factor_common_prefix() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let rule = r#"
+        (message.from.is_bot and message.text any {"博彩"}) or
+        (message.from.is_bot and message.text any {"广告"}) or
+        (message.from.is_bot)
+    "#;
+    let json_messages = [
+        r#"{"text": "博彩", "from": {"id": 1, "is_bot": true, "first_name": "Bot"}}"#,
+        r#"{"text": "广告", "from": {"id": 1, "is_bot": false, "first_name": "User"}}"#,
+        r#"{"text": "你好", "from": {"id": 1, "is_bot": true, "first_name": "Bot"}}"#,
+        r#"{"text": "你好"}"#,
+    ];
+    let messages = json_messages
+        .iter()
+        .map(|json| serde_json::from_str::<Message>(json).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut matcher = compile_rule(rule).unwrap();
+    let before = messages
+        .iter()
+        .map(|m| matcher.match_message(m).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(1, matcher.factor_common_prefix());
+    assert_eq!(1, matcher.common_prefix.len());
+    assert_eq!(
+        vec![1, 1, 0],
+        matcher.groups.iter().map(|g| g.len()).collect::<Vec<_>>()
+    );
+
+    let after = messages
+        .iter()
+        .map(|m| matcher.match_message(m).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(vec![true, false, true, false], before);
+    assert_eq!(before, after);
+
+    // 没有共同前置条件时不提取。
+    let mut matcher = compile_rule(r#"(message.text any {"a"}) or (message.from.is_bot)"#).unwrap();
+    assert_eq!(0, matcher.factor_common_prefix());
+}
+
+#[cfg(feature = "homoglyph")]
+#[test]
+fn test_fold_homoglyphs() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    // 前两个字母是西里尔字母。
+    let message = Message {
+        text: Some(String::from("Join саsino now")),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.text any {"casino"})"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+
+    matcher.options.fold_homoglyphs = true;
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_new_chat_members_usernames() {
+    let mass_join = r#"{"new_chat_members": [
+        {"id": 1, "is_bot": false, "first_name": "A", "username": "alice"},
+        {"id": 2, "is_bot": false, "first_name": "B"},
+        {"id": 3, "is_bot": false, "first_name": "C", "username": "spam_bot_shop"}
+    ]}"#;
+    let normal_join = r#"{"new_chat_members": [
+        {"id": 1, "is_bot": false, "first_name": "A", "username": "alice"}
+    ]}"#;
+
+    let rule = r#"(message.new_chat_members.usernames any {"spam"})"#;
+    assert!(rule_match_json(rule, mass_join).unwrap());
+    assert!(!rule_match_json(rule, normal_join).unwrap());
+    assert!(!rule_match_json(rule, r#"{"text": "spam"}"#).unwrap());
+
+    let rule = r#"(message.new_chat_members.usernames all {"a"})"#;
+    assert!(rule_match_json(rule, mass_join).unwrap());
+    let rule = r#"(message.new_chat_members.usernames all {"alice"})"#;
+    assert!(!rule_match_json(rule, mass_join).unwrap());
+    assert!(rule_match_json(rule, normal_join).unwrap());
+}
+
+#[test]
+fn test_message_id() {
+    let rule = r#"(message.message_id in {100 200 300})"#;
+    assert!(rule_match_json(rule, r#"{"message_id": 200}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"message_id": 201}"#).unwrap());
+
+    let rule = r#"(message.message_id gt 100)"#;
+    assert!(rule_match_json(rule, r#"{"message_id": 101}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"message_id": 100}"#).unwrap());
+}
+
+#[test]
+fn test_optional_cont() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let mut matcher =
+        compile_rule(r#"(message.text any {"博彩"} and message.text any {"招聘"})"#).unwrap();
+    matcher.groups[0][1].is_optional = true;
+
+    let message1 = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        ..Default::default()
+    };
+    let message2 = Message {
+        text: Some(String::from("东南亚博彩")),
+        ..Default::default()
+    };
+    let message3 = Message {
+        text: Some(String::from("东南亚招聘")),
+        ..Default::default()
+    };
+
+    assert!(matcher.match_message(&message1).unwrap());
+    assert!(matcher.match_message(&message2).unwrap());
+    assert!(!matcher.match_message(&message3).unwrap());
+
+    assert_eq!(2.0, matcher.score(&message1).unwrap());
+    assert_eq!(1.0, matcher.score(&message2).unwrap());
+    assert_eq!(0.0, matcher.score(&message3).unwrap());
+}
+
+#[test]
+fn test_all_optional_group() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let mut matcher = compile_rule(
+        r#"(message.text any {"博彩"} and message.text any {"招聘"}) or (message.text any {"承接"})"#,
+    )
+    .unwrap();
+    matcher.groups[0][0].is_optional = true;
+    matcher.groups[0][1].is_optional = true;
+    matcher.weights = vec![2.0];
+    let message_text = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    // 全部由可选条件构成的组，至少一个可选条件成立时才匹配成功。
+    assert!(!matcher.match_message(&message_text("你好")).unwrap());
+    assert_eq!(0.0, matcher.score(&message_text("你好")).unwrap());
+    assert!(!matcher.explain(&message_text("你好")).unwrap().is_matched);
+
+    let message = message_text("东南亚博彩");
+    assert!(matcher.match_message(&message).unwrap());
+    assert!(matcher.explain(&message).unwrap().is_matched);
+    assert_eq!(
+        Some(0),
+        matcher
+            .match_message_detailed(&message)
+            .unwrap()
+            .decisive_group
+    );
+    assert_eq!(4.0, matcher.score(&message).unwrap());
+    // 每个成立的可选条件额外增加一次组的权重。
+    assert_eq!(6.0, matcher.score(&message_text("东南亚博彩招聘")).unwrap());
+
+    // 提取共同前置条件不能留下只有可选条件的组。
+    let mut matcher = compile_rule(
+        r#"(message.text any {"东南亚"} and message.text any {"博彩"}) or (message.text any {"东南亚"} and message.text any {"招聘"})"#,
+    )
+    .unwrap();
+    matcher.groups[0][1].is_optional = true;
+    matcher.groups[1][1].is_optional = true;
+    assert_eq!(0, matcher.factor_common_prefix());
+    assert!(matcher.match_message(&message_text("东南亚")).unwrap());
+}
+
+#[test]
+fn test_audio_performer_and_title() {
+    let json_data = r#"{"audio": {"duration": 180, "performer": "Top Hits | t.me/spam_channel", "title": "加微信领资源"}}"#;
+
+    let rule = r#"(message.audio.performer any {"t.me/"})"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.audio.title all {"微信" "资源"})"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.audio.title hd "加微信")"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+    let rule = r#"(message.audio.title any {"t.me/"})"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_field_ref() {
+    let rule = r#"(message.text.len gt field:message.caption.len)"#;
+    assert!(rule_match_json(rule, r#"{"text": "Hello world", "caption": "Hi"}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"text": "Hi", "caption": "Hello world"}"#).unwrap());
+    // 被引用的字段不存在。
+    assert!(!rule_match_json(rule, r#"{"text": "Hello world"}"#).unwrap());
+    let rule = r#"(not message.text.len gt field:message.caption.len)"#;
+    assert!(rule_match_json(rule, r#"{"text": "Hello world"}"#).unwrap());
+
+    let rule = r#"(message.text eq field:message.caption.len)"#;
+    assert!(rule_match_json(rule, r#"{"text": "Hello"}"#).is_err());
+    let rule = r#"(message.text.len gt field:message.unknown)"#;
+    assert!(rule_match_json(rule, r#"{"text": "Hello"}"#).is_err());
+}
+
+#[test]
+fn test_braces_in_values() {
+    // 引号中的大括号不需要转义。
+    let rule = r#"(message.text any {"end}" "{start"})"#;
+    assert!(rule_match_json(rule, r#"{"text": "the end}"}"#).unwrap());
+    assert!(rule_match_json(rule, r#"{"text": "{start here"}"#).unwrap());
+
+    // 大括号前的反斜杠保持原样。
+    let rule = r#"(message.text any {"end\}"})"#;
+    assert!(!rule_match_json(rule, r#"{"text": "the end}"}"#).unwrap());
+    assert!(rule_match_json(rule, r#"{"text": "the end\\}"}"#).unwrap());
+
+    // 其它的反斜杠保持原样。
+    let rule = r#"(message.text eq "a\b")"#;
+    assert!(rule_match_json(rule, r#"{"text": "a\\b"}"#).unwrap());
+}
+
+#[test]
+fn test_find_contradictions() {
+    use matchingram::compile_rule;
+    use matchingram::matches::{Contradiction, Field};
+
+    let matcher = compile_rule(
+        r#"(message.text.len gt 10 and message.text any {"a"} and message.text.len le 5) or (message.text.len eq 3 and message.text.len eq 4)"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            Contradiction {
+                group: 0,
+                field: Field::MessageTextLen,
+                conts: (0, 2)
+            },
+            Contradiction {
+                group: 1,
+                field: Field::MessageTextLen,
+                conts: (0, 1)
+            }
+        ],
+        matcher.find_contradictions()
+    );
+
+    let matcher = compile_rule(
+        r#"(message.text.len ge 5 and message.text.len le 5) or (message.text.len gt 5 and message.caption.len le 5)"#,
+    )
+    .unwrap();
+    assert!(matcher.find_contradictions().is_empty());
+
+    let matcher = compile_rule(r#"(message.text.len gt 5 and message.text.len le 5)"#).unwrap();
+    assert_eq!(1, matcher.find_contradictions().len());
+    let matcher = compile_rule(r#"(message.text.len gt 5 and not message.text.len le 5)"#).unwrap();
+    assert!(matcher.find_contradictions().is_empty());
+}
+
+#[test]
+fn test_decode_encoded() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    // `Y2FzaW5v` 是 `casino` 的 base64 编码。
+    let message1 = Message {
+        text: Some(String::from("欢迎访问 Y2FzaW5v 官网")),
+        ..Default::default()
+    };
+    let message2 = Message {
+        text: Some(String::from("https://example.com/%E5%8D%9A%E5%BD%A9")),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.text any {"casino" "博彩"})"#).unwrap();
+    assert!(!matcher.match_message(&message1).unwrap());
+    assert!(!matcher.match_message(&message2).unwrap());
+
+    matcher.options.decode_encoded = true;
+    assert!(matcher.match_message(&message1).unwrap());
+    assert!(matcher.match_message(&message2).unwrap());
+
+    let mut matcher = compile_rule(r#"(message.text eq "casino")"#).unwrap();
+    matcher.options.decode_encoded = true;
+    assert!(!matcher.match_message(&message1).unwrap());
+}
+
+#[test]
+fn test_all_text() {
+    let rule = r#"(message.all_text any {"scam"})"#;
+
+    let json_data = r#"{"caption": "免费资源", "document": {"file_name": "free_scam_tool.zip"}}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+    let json_data = r#"{"caption": "免费资源", "document": {"file_name": "tool.zip"}}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let json_data = r#"{"text": "hi", "from": {"id": 1, "is_bot": false, "first_name": "A", "username": "scam_bot"}}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.all_text all {"免费" "tool"})"#;
+    let json_data = r#"{"caption": "免费资源", "document": {"file_name": "tool.zip"}}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_match_group() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+    use matchingram::Error;
+
+    let matcher = compile_rule(
+        r#"(message.text any {"柬埔寨" "东南亚"} and message.text any {"菠菜" "博彩"}) or (message.text all {"承接" "广告"})"#,
+    )
+    .unwrap();
+    let message = Message {
+        text: Some(String::from("承接博彩广告业务")),
+        ..Default::default()
+    };
+
+    assert!(!matcher.match_group(0, &message).unwrap());
+    assert!(matcher.match_group(1, &message).unwrap());
+    assert_eq!(
+        Err(Error::GroupIndexOutOfRange { index: 2, len: 2 }),
+        matcher.match_group(2, &message)
+    );
+}
+
+#[test]
+fn test_try_from_str() {
+    use matchingram::matches::Field;
+    use matchingram::operator::Operator;
+    use matchingram::Error;
+    use std::convert::TryFrom;
+
+    assert_eq!(
+        Ok(Field::MessageTextLen),
+        Field::try_from("message.text.len")
+    );
+    assert_eq!(
+        Err(Error::UnknownField {
+            field: String::from("message.txt")
+        }),
+        Field::try_from("message.txt")
+    );
+
+    assert_eq!(Ok(Operator::Any), Operator::try_from("any"));
+    assert_eq!(
+        Err(Error::UnknownOperator {
+            operator: String::from("contain")
+        }),
+        Operator::try_from("contain")
+    );
+}
+
+#[test]
+fn test_forward_date() {
+    let rule = r#"(message.forward_date gt 0)"#;
+    assert!(rule_match_json(rule, r#"{"date": 1700000000, "forward_date": 1600000000}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"date": 1700000000}"#).unwrap());
+
+    // 转发一年以前的消息。
+    let rule = r#"(message.forward_age ge 31_536_000)"#;
+    assert!(rule_match_json(rule, r#"{"date": 1700000000, "forward_date": 1600000000}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"date": 1700000000, "forward_date": 1699990000}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"forward_date": 1600000000}"#).unwrap());
+}
+
+#[test]
+fn test_in_value_set() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let rule = format!(
+        "(message.message_id in {{{}}})",
+        (1..=10000)
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let mut matcher = compile_rule(rule).unwrap();
+    let value_set = matcher.groups[0][0].value_set.as_ref().unwrap();
+    assert_eq!(10000, value_set.integers.len());
+
+    let message = Message {
+        message_id: 9999,
+        ..Default::default()
+    };
+    assert!(matcher.match_message(&message).unwrap());
+    let message = Message {
+        message_id: 10001,
+        ..Default::default()
+    };
+    assert!(!matcher.match_message(&message).unwrap());
+
+    // 未预先准备集合的条件同样可以匹配。
+    matcher.groups[0][0].value_set = None;
+    assert!(!matcher.match_message(&message).unwrap());
+
+    let rule = r#"(message.from.first_name in {"小明" "小红"})"#;
+    let json_data = r#"{"from": {"id": 1, "is_bot": false, "first_name": "小红"}}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_chat_id_negative() {
+    let supergroup = r#"{"chat": {"id": -1001234567890, "type": "supergroup"}}"#;
+    let other_supergroup = r#"{"chat": {"id": -1009876543210, "type": "supergroup"}}"#;
+
+    let rule = r#"(message.chat.id eq -1001234567890)"#;
+    assert!(rule_match_json(rule, supergroup).unwrap());
+    assert!(!rule_match_json(rule, other_supergroup).unwrap());
+    assert!(!rule_match_json(rule, r#"{"text": "hi"}"#).unwrap());
+
+    let rule = r#"(message.chat.id in {-1001234567890 -1001111111111})"#;
+    assert!(rule_match_json(rule, supergroup).unwrap());
+    assert!(!rule_match_json(rule, other_supergroup).unwrap());
+
+    let rule = r#"(message.chat.id in {-1_001_234_567_890, 10086})"#;
+    assert!(rule_match_json(rule, supergroup).unwrap());
+
+    let rule = r#"(message.chat.id le -1000000000000)"#;
+    assert!(rule_match_json(rule, supergroup).unwrap());
+    assert!(!rule_match_json(rule, r#"{"chat": {"id": 10086, "type": "private"}}"#).unwrap());
+
+    let rule = r#"(message.chat.id lt 0)"#;
+    assert!(rule_match_json(rule, supergroup).unwrap());
+    assert!(!rule_match_json(rule, r#"{"chat": {"id": 10086, "type": "private"}}"#).unwrap());
+}
+
+#[test]
+fn test_optimize() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Field;
+
+    let rule = r#"(message.text any {"博彩"} and message.from.first_name hd "A" and message.text.len gt 3 and message.forward_from_chat) or (message.text all {"承接" "广告"} and not message.animation)"#;
+    let matcher = compile_rule(rule).unwrap();
+    let mut optimized_matcher = compile_rule(rule).unwrap();
+    optimized_matcher.optimize();
+
+    let fields = optimized_matcher.groups[0]
+        .iter()
+        .map(|cont| cont.field)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            Field::MessageForwardFromChat,
+            Field::MessageTextLen,
+            Field::MessageFromFirstName,
+            Field::MessageText
+        ],
+        fields
+    );
+    assert_eq!(
+        Field::MessageAnimation,
+        optimized_matcher.groups[1][0].field
+    );
+
+    let from = r#""forward_from_chat": {"id": -1001, "type": "channel"}, "from": {"id": 1, "is_bot": false, "first_name": "A"}"#;
+    for json_data in [
+        format!(r#"{{"text": "东南亚博彩招聘", {}}}"#, from),
+        String::from(r#"{"text": "东南亚博彩招聘"}"#),
+        String::from(r#"{"text": "承接博彩广告业务"}"#),
+        String::from(r#"{"text": "承接博彩广告业务", "animation": {"duration": 1}}"#),
+        format!(r#"{{"text": "你好", {}}}"#, from),
+    ] {
+        assert_eq!(
+            matchingram::matcher_match_json(&matcher, &json_data).unwrap(),
+            matchingram::matcher_match_json(&optimized_matcher, &json_data).unwrap()
+        );
+    }
+}
+
+#[cfg(feature = "leetspeak")]
+#[test]
+fn test_fold_leetspeak() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let message = Message {
+        text: Some(String::from("Best C4S1N0 in town, 100% win")),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.text any {"casino"})"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+
+    matcher.options.fold_leetspeak = true;
+    assert!(matcher.match_message(&message).unwrap());
+
+    // `1` 被同时视作 `i` 和 `l`。
+    let mut matcher = compile_rule(r#"(message.text all {"casino" "100%" "wall"})"#).unwrap();
+    matcher.options.fold_leetspeak = true;
+    let message = Message {
+        text: Some(String::from("c4s1n0 w411")),
+        ..Default::default()
+    };
+    assert!(!matcher.match_message(&message).unwrap());
+    let message = Message {
+        text: Some(String::from("c4s1n0 w411 100%")),
+        ..Default::default()
+    };
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_rule_limits() {
+    use matchingram::matches::{Field, Matcher, RuleLimits};
+    use matchingram::Error;
+
+    let limits = RuleLimits {
+        min_needle_length: 2,
+    };
+    let rule = r#"(message.text any {"博彩" "a"}) or (message.text all {"承接" "广告"})"#;
+
+    assert_eq!(
+        Err(Error::NeedleTooShort {
+            field: Field::MessageText,
+            needle: String::from("a"),
+            min: 2
+        }),
+        Matcher::from_rule_with_limits(rule, &limits).map(|_| ())
+    );
+    // 默认不限制。
+    assert!(Matcher::from_rule_with_limits(rule, &RuleLimits::default()).is_ok());
+    // 仅检查 `any`/`all` 运算的值。
+    let rule = r#"(message.text eq "a" and message.from.first_name hd "A")"#;
+    assert!(Matcher::from_rule_with_limits(rule, &limits).is_ok());
+}
+
+#[test]
+fn test_reply_to_message_from() {
+    let rule = r#"(message.reply_to_message.from.id eq 10086)"#;
+    let json = r#"{
+        "message_id": 2,
+        "reply_to_message": {
+            "message_id": 1,
+            "from": {"id": 10086, "is_bot": false, "first_name": "Target", "username": "target"},
+            "text": "hello"
+        },
+        "text": "spam"
+    }"#;
+    assert!(rule_match_json(rule, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.reply_to_message.from.username in {"foo" "target"})"#,
+        json
+    )
+    .unwrap());
+    assert!(!rule_match_json(r#"(message.reply_to_message.from.id in {1 2 3})"#, json).unwrap());
+
+    // 没有回复消息或被回复的消息没有发送者。
+    let json = r#"{"message_id": 2, "text": "spam"}"#;
+    assert!(!rule_match_json(rule, json).unwrap());
+    assert!(rule_match_json(r#"(not message.reply_to_message.from.id eq 10086)"#, json).unwrap());
+    let json = r#"{"message_id": 2, "reply_to_message": {"message_id": 1}, "text": "spam"}"#;
+    assert!(!rule_match_json(rule, json).unwrap());
+    assert!(!rule_match_json(
+        r#"(message.reply_to_message.from.username eq "target")"#,
+        json
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_simple_fields_dispatch() {
+    let json = r#"{
+        "message_id": 1,
+        "chat": {"id": -1001, "type": "supergroup"},
+        "from": {"id": 10086, "is_bot": false, "first_name": "Hentioe", "language_code": "zh-hans"},
+        "audio": {"file_id": "1", "file_unique_id": "1", "duration": 60, "performer": "Singer", "mime_type": "audio/mpeg", "file_size": 1024},
+        "caption": "audio"
+    }"#;
+
+    let cases = [
+        (r#"(message.from.id eq 10086)"#, true),
+        (r#"(message.from.id gt 10085)"#, true),
+        (r#"(message.from.id ge 10087)"#, false),
+        (r#"(message.from.id le 10086)"#, true),
+        (r#"(message.from.first_name eq "Hentioe")"#, true),
+        (r#"(message.from.first_name in {"Foo" "Hentioe"})"#, true),
+        (r#"(message.from.first_name any {"tio" "bar"})"#, true),
+        (r#"(message.from.first_name all {"tio" "bar"})"#, false),
+        (r#"(message.from.first_name hd "Hen")"#, true),
+        (r#"(message.from.language_code hd "zh")"#, true),
+        (r#"(message.chat.id in {-1001 -1002})"#, true),
+        (r#"(message.audio.performer any {"Sing"})"#, true),
+        (r#"(message.audio.mime_type in {"audio/mpeg"})"#, true),
+        (r#"(message.audio.file_size ge 1024)"#, true),
+        // 字段不存在。
+        (r#"(message.audio.title eq "Song")"#, false),
+        (r#"(message.video.duration gt 0)"#, false),
+        (r#"(not message.video.duration gt 0)"#, true),
+    ];
+
+    for (rule, expected) in &cases {
+        assert_eq!(*expected, rule_match_json(*rule, json).unwrap(), "{}", rule);
+    }
+}
+
+#[test]
+fn test_message_text_markdown() {
+    let json = r#"{
+        "message_id": 1,
+        "text": "🎉 点击领取奖品",
+        "entities": [
+            {"type": "bold", "offset": 3, "length": 2},
+            {"type": "text_link", "offset": 3, "length": 6, "url": "https://evil.com/prize"}
+        ]
+    }"#;
+
+    assert!(rule_match_json(r#"(message.text.markdown any {"evil.com"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text any {"evil.com"})"#, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.text.markdown eq "🎉 [*点击*领取奖品](https://evil.com/prize)")"#,
+        json
+    )
+    .unwrap());
+
+    // 没有实体时与文本相同。
+    let json = r#"{"message_id": 1, "text": "hello"}"#;
+    assert!(rule_match_json(r#"(message.text.markdown eq "hello")"#, json).unwrap());
+    // 没有文本。
+    let json = r#"{"message_id": 1}"#;
+    assert!(!rule_match_json(r#"(message.text.markdown any {"hello"})"#, json).unwrap());
+}
+
+#[test]
+fn test_normalize_line_endings() {
+    use matchingram::matches::{Field, Matcher, RowRelation};
+    use matchingram::models::Message;
+    use matchingram::operator::Operator;
+
+    let message = Message {
+        text: Some(String::from("招聘\r\n薪资面议\r\n私聊")),
+        caption: Some(String::from("招聘\r薪资面议\r私聊")),
+        ..Default::default()
+    };
+    let template = String::from("招聘\n薪资面议\n私聊");
+
+    let rows = [(Field::MessageText, Operator::Eq, vec![template.clone()])];
+    let mut matcher = Matcher::from_table(&rows, RowRelation::And).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+    matcher.options.normalize_line_endings = true;
+    assert!(matcher.match_message(&message).unwrap());
+
+    let rows = [(Field::MessageCaption, Operator::Eq, vec![template])];
+    let mut matcher = Matcher::from_table(&rows, RowRelation::And).unwrap();
+    matcher.options.normalize_line_endings = true;
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_count_matching_groups() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let matcher = compile_rule(
+        r#"(message.text any {"博彩"}) or (message.text any {"招聘"}) or (message.text all {"承接" "广告"})"#,
+    )
+    .unwrap();
+
+    let message = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        ..Default::default()
+    };
+    assert_eq!(2, matcher.count_matching_groups(&message).unwrap());
+
+    let message = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+    assert_eq!(0, matcher.count_matching_groups(&message).unwrap());
+}
+
+#[test]
+fn test_message_mentions() {
+    let json = r#"{
+        "message_id": 1,
+        "text": "@admin 有人发广告，请 @mod_1 处理，联系 me@example.com @admin"
+    }"#;
+
+    assert!(rule_match_json(r#"(message.mentions in {"admin" "mod"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.mentions in {"mod" "example"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.mentions any {"mod"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.mentions any {"example"})"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "text": "没有提及"}"#;
+    assert!(!rule_match_json(r#"(message.mentions in {"admin" "mod"})"#, json).unwrap());
+
+    let message = matchingram::models::Message {
+        text: Some(String::from(
+            "@admin 有人发广告，请 @mod_1 处理，联系 me@example.com @admin",
+        )),
+        ..Default::default()
+    };
+    assert_eq!(vec!["admin", "mod_1"], message.mentions());
+}
+
+#[test]
+fn test_message_caption_hd_td() {
+    let json = r#"{"message_id": 1, "caption": "Breaking: 东南亚博彩招聘 [ad]"}"#;
+
+    assert!(rule_match_json(r#"(message.caption hd "Breaking")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.caption hd "博彩")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.caption td "[ad]")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.caption td "Breaking")"#, json).unwrap());
+}
+
+#[test]
+fn test_message_caption_in() {
+    let json = r#"{"message_id": 1, "caption": "免费领取"}"#;
+
+    assert!(rule_match_json(r#"(message.caption in {"免费领取" "点击链接"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.caption in {"免费" "领取"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.caption in {"免费" "领取"})"#, json).unwrap());
+}
+
+#[test]
+fn test_evaluator_across_threads() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Evaluator;
+    use matchingram::models::Message;
+    use std::sync::Arc;
+    use std::thread;
+
+    let matcher = Arc::new(
+        compile_rule(r#"(message.text any {"博彩"}) or (message.text all {"承接" "广告"})"#)
+            .unwrap(),
+    );
+    let texts = ["东南亚博彩招聘", "承接各类广告", "你好", "承接"];
+
+    let handles = texts
+        .iter()
+        .map(|text| {
+            let matcher = Arc::clone(&matcher);
+            let text = String::from(*text);
+
+            thread::spawn(move || {
+                let evaluator = Evaluator::new(&matcher);
+                let message = Message {
+                    text: Some(text),
+                    ..Default::default()
+                };
+
+                evaluator.match_message(&message).unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(vec![true, true, false, false], results);
+}
+
+#[test]
+fn test_shared_matcher_across_threads() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+    use std::thread;
+
+    let matcher = compile_rule(r#"(message.text any {"博彩"})"#).unwrap();
+    let matcher = &matcher;
+    let message = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    let (a, b) = thread::scope(|s| {
+        let a = s
+            .spawn(|| (0..100).all(|_| matcher.match_message(&message("东南亚博彩招聘")).unwrap()));
+        let b = s.spawn(|| (0..100).any(|_| matcher.match_message(&message("你好")).unwrap()));
+
+        (a.join().unwrap(), b.join().unwrap())
+    });
+
+    assert!(a);
+    assert!(!b);
+}
+
+#[test]
+fn test_match_huge_groups() {
+    use matchingram::matches::{Cont, Matcher, Value};
+    use matchingram::models::Message;
+
+    let groups = (0..100_000)
+        .map(|i| {
+            let value = vec![Value::from_str(&format!("text-{}", i))];
+            vec![Cont::new(
+                false,
+                String::from("message.text"),
+                String::from("eq"),
+                value,
+            )
+            .unwrap()]
+        })
+        .collect();
+    let matcher = Matcher::new(groups);
+    let message = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    let result = matcher
+        .match_message_detailed(&message("text-99999"))
+        .unwrap();
+    assert_eq!(Some(99_999), result.decisive_group);
+    assert!(!matcher.match_message(&message("text")).unwrap());
+}
+
+#[test]
+fn test_url_resolver() {
+    use matchingram::compile_rule;
+    use matchingram::matches::UrlResolver;
+    use matchingram::models::Message;
+
+    let message = Message {
+        text: Some(String::from("免费领取 bit.ly/x 先到先得")),
+        ..Default::default()
+    };
+    let mut matcher = compile_rule(r#"(message.text any {"evil.com"})"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+
+    matcher.options.url_resolver = Some(UrlResolver::new(|url| {
+        let url = url.trim_start_matches("https://");
+        if url == "bit.ly/x" {
+            Some(String::from("https://evil.com/landing"))
+        } else {
+            None
+        }
+    }));
+    assert!(matcher.match_message(&message).unwrap());
+
+    let message = Message {
+        text: Some(String::from("免费领取 https://bit.ly/y 先到先得")),
+        ..Default::default()
+    };
+    assert!(!matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_large_file_size() {
+    // 1GB 的文件。
+    let json = r#"{"message_id": 1, "document": {"file_name": "a.zip", "file_size": 1073741824}}"#;
+
+    assert!(!rule_match_json(r#"(message.document.file_size gt 3221225472)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.document.file_size ge 3221225472)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.document.file_size le 3221225472)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.document.file_size eq 3221225472)"#, json).unwrap());
+
+    // 超过 `i32::MAX` 的 3GB 文件。
+    let json = r#"{"message_id": 1, "video": {"duration": 1, "file_size": 3221225472}}"#;
+
+    assert!(rule_match_json(r#"(message.video.file_size eq 3221225472)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.video.file_size gt 1073741824)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.video.file_size ge 3_221_225_472)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.video.file_size le 1073741824)"#, json).unwrap());
+}
+
+#[test]
+fn test_has_bidi_control() {
+    let rule = r#"(message.text.has_bidi_control)"#;
+
+    let json_data = r#"{"text": "免费\u202e领取"}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+    let json_data = r#"{"text": "免费领取"}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+
+    let rule = r#"(message.document.file_name.has_bidi_control)"#;
+
+    let json_data = r#"{"document": {"file_name": "invoice\u202efdp.exe"}}"#;
+    assert!(rule_match_json(rule, json_data).unwrap());
+    let json_data = r#"{"document": {"file_name": "invoice.pdf"}}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+    let json_data = r#"{"text": "invoice\u202efdp.exe"}"#;
+    assert!(!rule_match_json(rule, json_data).unwrap());
+}
+
+#[test]
+fn test_explain_count() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Field;
+    use matchingram::models::Message;
+
+    let matcher = compile_rule(
+        r#"(message.text all {"承接" "广告" "推广" "代理" "引流"} and not message.text.len gt 100)"#,
+    )
+    .unwrap();
+    let message = Message {
+        text: Some(String::from("承接各类广告推广")),
+        ..Default::default()
+    };
+    let trace = matcher.explain(&message).unwrap();
+
+    assert!(!trace.is_matched);
+    assert_eq!(2, trace.conts.len());
+    assert_eq!(Some(0), trace.conts[0].group);
+    assert_eq!(Field::MessageText, trace.conts[0].field);
+    assert!(!trace.conts[0].is_matched);
+    assert_eq!(Some(3), trace.conts[0].count());
+    assert!(trace.conts[1].is_matched);
+    assert_eq!(None, trace.conts[1].count());
+}
+
+#[test]
+fn test_match_message_detailed() {
+    use matchingram::compile_rule;
+    use matchingram::matches::MatchResult;
+    use matchingram::models::Message;
+
+    let matcher = compile_rule(
+        r#"(message.text any {"博彩"}) or (message.text any {"招聘"}) or (message.text all {"承接" "广告"})"#,
+    )
+    .unwrap();
+    let message_text = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        MatchResult {
+            matched: true,
+            decisive_group: Some(1)
+        },
+        matcher
+            .match_message_detailed(&message_text("高薪招聘"))
+            .unwrap()
+    );
+    // 多个组匹配时，决定结果的是第一个组。
+    assert_eq!(
+        Some(0),
+        matcher
+            .match_message_detailed(&message_text("博彩招聘"))
+            .unwrap()
+            .decisive_group
+    );
+    assert_eq!(
+        MatchResult {
+            matched: false,
+            decisive_group: None
+        },
+        matcher
+            .match_message_detailed(&message_text("你好"))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_match_message_detail() {
+    use matchingram::compile_rule;
+    use matchingram::matches::MatchReport;
+    use matchingram::models::Message;
+
+    let mut matcher = compile_rule(
+        r#"(message.text any {"博彩"}) or (message.text all {"承接" "广告"} and message.text.len le 10 and message.text any {"微信"})"#,
+    )
+    .unwrap();
+    // 第三个条件可选，不成立时也不影响匹配结果。
+    matcher.groups[1][2].is_optional = true;
+    let message_text = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Some(MatchReport {
+            group_index: 1,
+            matched: vec![0, 1]
+        }),
+        matcher
+            .match_message_detail(&message_text("承接各类广告"))
+            .unwrap()
+    );
+    assert_eq!(
+        vec![0, 1, 2],
+        matcher
+            .match_message_detail(&message_text("承接广告加微信"))
+            .unwrap()
+            .unwrap()
+            .matched
+    );
+    assert_eq!(
+        None,
+        matcher.match_message_detail(&message_text("你好")).unwrap()
+    );
+    assert!(!matcher.match_message(&message_text("你好")).unwrap());
+}
+
+#[test]
+fn test_td_operator() {
+    let json = r#"{
+        "message_id": 1,
+        "from": {"id": 1, "is_bot": false, "first_name": "Hentioe", "last_name": "Bot"},
+        "document": {"file_name": "invoice.pdf", "mime_type": "application/pdf"},
+        "forward_from_chat": {"id": -1001, "type": "channel", "title": "每日新闻"}
+    }"#;
+
+    assert!(rule_match_json(r#"(message.document.file_name td ".pdf")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.document.file_name td ".exe")"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.document.file_name td ".exe")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(not message.document.file_name td ".pdf")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.document.mime_type td "/pdf")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.first_name td "tioe")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.full_name td "Bot")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.forward_from_chat.title td "新闻")"#, json).unwrap());
+    // 字段不存在。
+    assert!(!rule_match_json(r#"(message.audio.title td "x")"#, json).unwrap());
+}
+
+#[test]
+fn test_document_contains() {
+    let json = r#"{
+        "message_id": 1,
+        "document": {"file_name": "2021-invoice-03.pdf", "mime_type": "Application/PDF"}
+    }"#;
+
+    let rule = r#"(message.document.file_name contains "invoice" and message.document.mime_type contains "pdf")"#;
+    assert!(rule_match_json(rule, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.document.mime_type contains "APPLICATION/")"#,
+        json
+    )
+    .unwrap());
+    // 文件名区分大小写。
+    assert!(!rule_match_json(r#"(message.document.file_name contains "INVOICE")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.document.file_name contains "receipt")"#, json).unwrap());
+    assert!(rule_match_json(
+        r#"(not message.document.file_name contains "receipt")"#,
+        json
+    )
+    .unwrap());
+    // 字段不存在。
+    assert!(!rule_match_json(
+        r#"(message.document.file_name contains "invoice")"#,
+        r#"{"message_id": 1}"#
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_contains_operator() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Field;
+    use matchingram::operator::Operator;
+    use matchingram::Error;
+
+    let json = r#"{
+        "message_id": 1,
+        "text": "今晚八点报名截止",
+        "caption": "限时报名",
+        "from": {"id": 1, "is_bot": false, "first_name": "Hentioe"}
+    }"#;
+
+    assert!(rule_match_json(r#"(message.text contains "报名")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text contains "博彩")"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.text contains "博彩")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(not message.text contains "报名")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.caption contains "报名")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.all_text contains "截止")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.first_name contains "tio")"#, json).unwrap());
+    assert_eq!(
+        Err(Error::SingleValueRequired {
+            field: Field::MessageText,
+            operator: Operator::Contains,
+            count: 2,
+        }),
+        compile_rule(r#"(message.text contains {"报名" "截止"})"#).map(|_| ())
+    );
+}
+
+#[test]
+fn test_text_utf16_len() {
+    // `👍` 是一个字符，但在 UTF-16 中占两个编码单元。
+    let json = r#"{"message_id": 1, "text": "好👍"}"#;
+
+    assert!(rule_match_json(r#"(message.text.len eq 2)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.utf16_len eq 3)"#, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.text.utf16_len gt field:message.text.len)"#,
+        json
+    )
+    .unwrap());
+    // 字段不存在。
+    assert!(!rule_match_json(r#"(message.text.utf16_len ge 0)"#, r#"{"message_id": 1}"#).unwrap());
+}
+
+#[test]
+fn test_not_in_operator() {
+    let json = r#"{
+        "message_id": 1,
+        "text": "你好",
+        "forward_from_chat": {"id": -1001, "type": "group", "title": "每日新闻"}
+    }"#;
+
+    assert!(rule_match_json(
+        r#"(message.forward_from_chat.type not_in {"channel" "supergroup"})"#,
+        json
+    )
+    .unwrap());
+    assert!(!rule_match_json(
+        r#"(message.forward_from_chat.type not_in {"channel" "group"})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(
+        r#"(not message.forward_from_chat.type not_in {"channel" "group"})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(r#"(message.text not_in {"在吗" "hello"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.message_id not_in {2 3})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.message_id not_in {1 2})"#, json).unwrap());
+
+    // 字段不存在时 `not_in` 成立，取反后不成立。
+    let json = r#"{"message_id": 1, "text": "你好"}"#;
+    assert!(rule_match_json(
+        r#"(message.forward_from_chat.type not_in {"channel"})"#,
+        json
+    )
+    .unwrap());
+    assert!(!rule_match_json(
+        r#"(not message.forward_from_chat.type not_in {"channel"})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(r#"(message.caption not_in {"广告"})"#, json).unwrap());
+    // 这与 `not ... in` 相同，但 `not ... in` 在取反前是因字段不存在而不成立。
+    assert!(rule_match_json(
+        r#"(not message.forward_from_chat.type in {"channel"})"#,
+        json
+    )
+    .unwrap());
+}
+
+#[test]
+fn test_strip_emoji_modifiers() {
+    use matchingram::compile_rule;
+    use matchingram::models::{Message, Sticker};
+
+    let message = Message {
+        sticker: Some(Sticker {
+            is_animated: false,
+            emoji: Some(String::from("👍🏽")),
+            set_name: None,
+        }),
+        ..Default::default()
+    };
+
+    let mut matcher = compile_rule(r#"(message.sticker.emoji eq "👍")"#).unwrap();
+    assert!(!matcher.match_message(&message).unwrap());
+    matcher.options.strip_emoji_modifiers = true;
+    assert!(matcher.match_message(&message).unwrap());
+
+    let mut matcher = compile_rule(r#"(message.sticker.emoji in {"👍" "👎"})"#).unwrap();
+    matcher.options.strip_emoji_modifiers = true;
+    assert!(matcher.match_message(&message).unwrap());
+}
+
+#[test]
+fn test_case_insensitive_operators() {
+    let json = r#"{
+        "message_id": 1,
+        "from": {"id": 1, "is_bot": false, "first_name": "ÉLODIE", "language_code": "en"},
+        "forward_from_chat": {"id": -1001, "type": "Channel", "title": "每日新闻"},
+        "document": {"file_name": "a.pdf", "mime_type": "Application/PDF"}
+    }"#;
+
+    assert!(rule_match_json(r#"(message.from.language_code ieq "EN")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.language_code eq "EN")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.language_code iin {"ZH" "EN"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.language_code iin {"ZH" "JA"})"#, json).unwrap());
+    // 非 ASCII 字符。
+    assert!(rule_match_json(r#"(message.from.first_name ieq "élodie")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.forward_from_chat.type iin {"channel"})"#, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.document.mime_type ieq "application/pdf")"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(
+        r#"(not message.document.mime_type iin {"image/png"})"#,
+        json
+    )
+    .unwrap());
+    // 字段不存在。
+    assert!(!rule_match_json(r#"(message.audio.mime_type ieq "audio/mpeg")"#, json).unwrap());
+}
+
+#[test]
+fn test_infinite_decimal() {
+    use matchingram::compile_rule;
+    use matchingram::Error;
+
+    // 溢出为无穷大的小数。
+    let rule = format!("(message.location.latitude gt {}.0)", "9".repeat(400));
+    assert_eq!(
+        Err(Error::InvalidValue {
+            value: String::from("inf"),
+            field: String::from("message.location.latitude"),
+        }),
+        compile_rule(rule).map(|_| ())
+    );
+    assert!(compile_rule("(message.location.latitude gt 90.0)").is_ok());
+}
+
+#[test]
+fn test_between_operator() {
+    use matchingram::compile_rule;
+    use matchingram::Error;
+
+    let json = r#"{
+        "message_id": 1,
+        "text": "你好",
+        "animation": {"duration": 10, "file_size": 2048},
+        "location": {"longitude": 120.5, "latitude": 30.2}
+    }"#;
+
+    assert!(rule_match_json(r#"(message.animation.duration between {3 30})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.animation.duration between {10 10})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.animation.duration between {11 30})"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.animation.duration between {11 30})"#, json).unwrap());
+    assert!(rule_match_json(
+        r#"(message.animation.file_size between {1_024 4_096})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(r#"(message.location.latitude between {30.0 31.0})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.len between {1 2})"#, json).unwrap());
+    // 下限大于上限时不成立。
+    assert!(!rule_match_json(r#"(message.animation.duration between {30 3})"#, json).unwrap());
+    // 字段不存在。
+    assert!(!rule_match_json(r#"(message.audio.duration between {3 30})"#, json).unwrap());
+
+    assert_eq!(
+        Err(Error::RangeRequiresTwoValues { count: 3 }),
+        compile_rule(r#"(message.animation.duration between {3 30 60})"#).map(|_| ())
+    );
+    assert_eq!(
+        Err(Error::RangeRequiresTwoValues { count: 1 }),
+        compile_rule(r#"(message.animation.duration between 3)"#).map(|_| ())
+    );
+}
+
+#[test]
+fn test_text_upper_ratio() {
+    let json = r#"{"message_id": 1, "text": "BUY NOW!!! CHEAP"}"#;
+    assert!(rule_match_json(r#"(message.text.upper_ratio ge 80)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.upper_ratio eq 100)"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "text": "Hello World"}"#;
+    assert!(!rule_match_json(r#"(message.text.upper_ratio ge 80)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.upper_ratio eq 20)"#, json).unwrap());
+
+    // 没有大小写之分的字符。
+    let json = r#"{"message_id": 1, "text": "你好 123"}"#;
+    assert!(rule_match_json(r#"(message.text.upper_ratio eq 0)"#, json).unwrap());
+}
+
+#[test]
+fn test_mime_type_td() {
+    let json = r#"{
+        "message_id": 1,
+        "animation": {"duration": 3, "mime_type": "video/mp4"},
+        "audio": {"duration": 3, "mime_type": "audio/mpeg"},
+        "document": {"file_name": "a.mp4", "mime_type": "video/mp4"},
+        "video": {"duration": 3, "mime_type": "video/mp4"},
+        "voice": {"duration": 3, "mime_type": "audio/ogg"}
+    }"#;
+
+    for field in &[
+        "message.animation.mime_type",
+        "message.document.mime_type",
+        "message.video.mime_type",
+    ] {
+        let rule = format!(r#"({} td "/mp4")"#, field);
+        assert!(rule_match_json(&rule, json).unwrap(), "{}", field);
+        let rule = format!(r#"(not {} td "/mp4")"#, field);
+        assert!(!rule_match_json(&rule, json).unwrap(), "{}", field);
+    }
+    assert!(rule_match_json(r#"(message.audio.mime_type td "/mpeg")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.voice.mime_type td "/mp4")"#, json).unwrap());
+}
+
+#[test]
+fn test_summary() {
+    use matchingram::compile_rule;
+
+    let matcher = compile_rule(
+        r#"(message.text any {"a" "b"} and not message.from.is_bot) or (message.caption hd "ad" and message.text.len between {1 10})"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        r#"Matches if (text contains any of ["a", "b"] AND sender is not a bot) OR (caption starts with "ad" AND text length is between 1 and 10)"#,
+        matcher.summary()
+    );
+
+    let mut matcher = compile_rule(
+        r#"(message.from.id gt 100 and message.text contains "x") or (message.from.id gt 100 and not message.text contains "y")"#,
+    )
+    .unwrap();
+    matcher.factor_common_prefix();
+    assert_eq!(
+        r#"Matches if (sender ID is greater than 100) AND ((text contains "x") OR (text does not contain "y"))"#,
+        matcher.summary()
+    );
+
+    let matcher = compile_rule(
+        r#"(message.photo and not message.text.is_blank) or (not message.document and message.text.len gt field:message.caption.len)"#,
+    )
+    .unwrap();
+    assert_eq!(
+        r#"Matches if (message has a photo AND text is not blank) OR (message does not have a document AND text length is greater than caption length)"#,
+        matcher.summary()
+    );
+}
+
+#[test]
+fn test_escaped_quotes() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Value;
+
+    let matcher = compile_rule(r#"(message.text contains "a \"b\" c")"#).unwrap();
+    assert_eq!(
+        Some(vec![Value::Letter(String::from(r#"a "b" c"#))]),
+        matcher.groups[0][0].value
+    );
+
+    let json = r#"{"message_id": 1, "text": "he said \"hi\" to C:\\"}"#;
+    assert!(rule_match_json(r#"(message.text contains "said \"hi\"")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text eq "he said \"hi\" to C:\\")"#, json).unwrap());
+}
+
+#[test]
+fn test_eq_set_operator() {
+    let rule = r#"(message.text eq_set {"win" "you"})"#;
+
+    assert!(rule_match_json(rule, r#"{"message_id": 1, "text": "you win"}"#).unwrap());
+    assert!(rule_match_json(rule, r#"{"message_id": 1, "text": " win\n you "}"#).unwrap());
+    // 重复的单词不影响结果。
+    assert!(rule_match_json(rule, r#"{"message_id": 1, "text": "you win you"}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"message_id": 1, "text": "you win now"}"#).unwrap());
+    assert!(!rule_match_json(rule, r#"{"message_id": 1, "text": "you"}"#).unwrap());
+    assert!(rule_match_json(
+        r#"(not message.text eq_set {"win" "you"})"#,
+        r#"{"message_id": 1, "text": "you win now"}"#
+    )
+    .unwrap());
+    assert!(!rule_match_json(rule, r#"{"message_id": 1}"#).unwrap());
+}
+
+#[test]
+fn test_explain_absent_field() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let matcher = compile_rule(r#"(not message.text any {"x"})"#).unwrap();
+
+    // 文本不存在，结果来自托管规则。
+    let message = Message::default();
+    let trace = matcher.explain(&message).unwrap();
+    assert!(trace.is_matched);
+    assert!(trace.conts[0].is_matched);
+    assert!(trace.conts[0].is_absent);
+
+    // 文本存在但不包含关键字，结果来自真实的比较。
+    let message = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+    let trace = matcher.explain(&message).unwrap();
+    assert!(trace.is_matched);
+    assert!(trace.conts[0].is_matched);
+    assert!(!trace.conts[0].is_absent);
+}
+
+#[test]
+fn test_duration_fields_numeric_operators() {
+    let json = r#"{
+        "message_id": 1,
+        "animation": {"file_id": "1", "file_unique_id": "1", "width": 1, "height": 1, "duration": 30},
+        "audio": {"file_id": "2", "file_unique_id": "2", "duration": 30},
+        "video": {"file_id": "3", "file_unique_id": "3", "width": 1, "height": 1, "duration": 30},
+        "voice": {"file_id": "4", "file_unique_id": "4", "duration": 30}
+    }"#;
+
+    let fields = [
+        "message.animation.duration",
+        "message.audio.duration",
+        "message.video.duration",
+        "message.voice.duration",
+    ];
+    let cases = [
+        ("eq 30", true),
+        ("eq 29", false),
+        ("gt 29", true),
+        ("gt 30", false),
+        ("lt 31", true),
+        ("lt 30", false),
+        ("ge 30", true),
+        ("ge 31", false),
+        ("le 30", true),
+        ("le 29", false),
+        ("between {30 60}", true),
+        ("between {1 29}", false),
+    ];
+
+    for field in &fields {
+        for (condition, expected) in &cases {
+            let rule = format!("({} {})", field, condition);
+            assert_eq!(
+                *expected,
+                rule_match_json(rule.as_str(), json).unwrap(),
+                "{}",
+                rule
+            );
+        }
+    }
+}
+
+#[test]
+fn test_field_only_conts() {
+    let photo = r#"{"message_id": 1, "photo": [{"file_id": "1", "file_unique_id": "1", "width": 1, "height": 1}]}"#;
+    let sticker = r#"{"message_id": 1, "sticker": {"file_id": "2", "file_unique_id": "2", "width": 1, "height": 1, "is_animated": false}}"#;
+    let text = r#"{"message_id": 1, "text": "hello"}"#;
+
+    let cases = [
+        ("(message.photo)", [true, false, false]),
+        ("(not message.sticker)", [true, false, true]),
+        ("(message.photo) or (message.sticker)", [true, true, false]),
+        (
+            "(message.photo and not message.sticker)",
+            [true, false, false],
+        ),
+    ];
+
+    for (rule, expected) in &cases {
+        for (json, expected) in [photo, sticker, text].iter().zip(expected) {
+            assert_eq!(
+                *expected,
+                rule_match_json(*rule, *json).unwrap(),
+                "{}",
+                rule
+            );
+        }
+    }
+}
+
+#[test]
+fn test_text_amount() {
+    let json = r#"{"message_id": 1, "text": "earn 5000 USD daily"}"#;
+    assert!(rule_match_json(r#"(message.text.has_amount)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.max_amount ge 5000)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text.max_amount eq 5000)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text.max_amount lt 5000)"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "text": "send 0.5 BTC or $100"}"#;
+    assert!(rule_match_json(r#"(message.text.max_amount between {99.5 100})"#, json).unwrap());
+
+    // 没有金额时字段不存在。
+    let json = r#"{"message_id": 1, "text": "call 10086"}"#;
+    assert!(!rule_match_json(r#"(message.text.has_amount)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text.max_amount ge 0)"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.text.max_amount ge 0)"#, json).unwrap());
+}
+
+#[test]
+fn test_regex_list_operators() {
+    let json = r#"{"message_id": 1, "text": "转账 5000 USDT 到 TXa8Y3k"}"#;
+
+    assert!(rule_match_json(r#"(message.text all_matches {"\d{4}" "(?i)usdt"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text all_matches {"\d{4}" "BTC"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.text any_matches {"\d{4}" "BTC"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text any_matches {"^BTC" "ETH$"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.text all_matches {"\d{4}" "BTC"})"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "caption": "Join t.me/spam now"}"#;
+    assert!(rule_match_json(r#"(message.caption any_matches {"t\.me/\w+"})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.text any_matches {"t\.me/\w+"})"#, json).unwrap());
+}
+
+#[test]
+fn test_regex_escaped_braces() {
+    let literal = r#"{"message_id": 1, "text": "a{2}"}"#;
+    let repeated = r#"{"message_id": 1, "text": "aa"}"#;
+
+    // 正则表达式原样收到 `\{` 和 `\}`，匹配字面的大括号。
+    let rule = r#"(message.text any_matches {"^a\{2\}$"})"#;
+    assert!(rule_match_json(rule, literal).unwrap());
+    assert!(!rule_match_json(rule, repeated).unwrap());
+
+    let rule = r#"(message.text any_matches {"^a{2}$"})"#;
+    assert!(!rule_match_json(rule, literal).unwrap());
+    assert!(rule_match_json(rule, repeated).unwrap());
+}
+
+#[test]
+fn test_regex_list_operators_invalid_pattern() {
+    use matchingram::{compile_rule, Error};
+
+    let r = compile_rule(r#"(message.text all_matches {"\d+" "(a" "b"})"#);
+    match r.unwrap_err() {
+        Error::InvalidPattern { index, pattern, .. } => {
+            assert_eq!(1, index);
+            assert_eq!("(a", pattern);
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+
+    let r = compile_rule(r#"(message.text any_matches {"a" "b" "(a+)+$"})"#);
+    assert_eq!(
+        "invalid pattern `(a+)+$` at index 2: nested quantifiers",
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_user_fields() {
+    let json = r#"{
+        "message_id": 1,
+        "from": {"id": 10086, "is_bot": false, "first_name": "Alice", "username": "alice"},
+        "forward_from": {"id": 10010, "is_bot": false, "first_name": "Bob", "username": "bob_spam"},
+        "via_bot": {"id": 20000, "is_bot": true, "first_name": "Gif Bot", "username": "gif"},
+        "text": "hello"
+    }"#;
+
+    for source in &["from", "forward_from", "via_bot"] {
+        let rule = format!(
+            "(message.{}.id gt 10000 and message.{}.id lt 30000)",
+            source, source
+        );
+        assert!(rule_match_json(&rule, json).unwrap());
+        let rule = format!(
+            "(message.{}.first_name any {{\"Alice\" \"Bob\" \"Gif\"}})",
+            source
+        );
+        assert!(rule_match_json(&rule, json).unwrap());
+    }
+    for source in &["forward_from", "via_bot"] {
+        let rule = format!("(message.{}.username in {{\"bob_spam\" \"gif\"}})", source);
+        assert!(rule_match_json(&rule, json).unwrap());
+    }
+
+    assert!(!rule_match_json(r#"(message.from.is_bot)"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.forward_from.is_bot)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.via_bot.is_bot)"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.id in {10086 10010})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.forward_from.username td "spam")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.via_bot.first_name ieq "GIF BOT")"#, json).unwrap());
+
+    // 用户不存在时字段不存在。
+    let json = r#"{"message_id": 1, "text": "hello"}"#;
+    for source in &["from", "forward_from", "via_bot"] {
+        let rule = format!("(message.{}.is_bot)", source);
+        assert!(!rule_match_json(&rule, json).unwrap());
+        let rule = format!("(message.{}.id gt 0)", source);
+        assert!(!rule_match_json(&rule, json).unwrap());
+        let rule = format!("(not message.{}.first_name eq \"Alice\")", source);
+        assert!(rule_match_json(&rule, json).unwrap());
+    }
+}
+
+#[test]
+fn test_poll_type_missing() {
+    let json = r#"{"message_id": 1, "poll": {"id": "1", "question": "x"}}"#;
+    assert!(rule_match_json(r#"(message.poll)"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.poll.type eq "quiz")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.poll.type in {"regular" "quiz"})"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "poll": {"id": "1", "question": "x", "type": ""}}"#;
+    assert!(rule_match_json(r#"(not message.poll.type eq "quiz")"#, json).unwrap());
+
+    let json = r#"{"message_id": 1, "poll": {"id": "1", "question": "x", "type": "quiz"}}"#;
+    assert!(rule_match_json(r#"(message.poll.type eq "quiz")"#, json).unwrap());
+}
+
+#[test]
+fn test_is_repeat() {
+    use matchingram::compile_rule;
+    use matchingram::matches::MessageHistory;
+    use matchingram::models::Message;
+    use std::sync::Arc;
+
+    // 只记得 10086 发送过“加群领红包”。
+    struct StubHistory;
+
+    impl MessageHistory for StubHistory {
+        fn is_repeat(&self, sender_id: i64, text: &str) -> bool {
+            sender_id == 10086 && text == "加群领红包"
+        }
+    }
+
+    let message = |id: i64, text: &str| {
+        let json = format!(
+            r#"{{"message_id": 1, "from": {{"id": {}, "is_bot": false, "first_name": "x"}}, "text": "{}"}}"#,
+            id, text
+        );
+
+        serde_json::from_str::<Message>(&json).unwrap()
+    };
+
+    let mut matcher = compile_rule(r#"(message.is_repeat and message.text.len le 10)"#).unwrap();
+    // 未提供消息历史时字段不存在。
+    assert!(!matcher
+        .match_message(&message(10086, "加群领红包"))
+        .unwrap());
+
+    matcher.options.message_history = Some(Arc::new(StubHistory));
+    assert!(matcher
+        .match_message(&message(10086, "加群领红包"))
+        .unwrap());
+    assert!(!matcher
+        .match_message(&message(10010, "加群领红包"))
+        .unwrap());
+    assert!(!matcher.match_message(&message(10086, "你好")).unwrap());
+}
+
+#[test]
+fn test_in_range() {
+    let json = r#"{"message_id": 42, "from": {"id": 10086, "is_bot": false, "first_name": "x"}}"#;
+
+    assert!(rule_match_json(r#"(message.from.id in {1..100 10000..20000})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.id in {10086..10086})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.id in {1..100 500})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.id not_in {1..100 500})"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.id not_in {10000..20000})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.message_id in {1..50})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.message_id not_in {43..50 1..41})"#, json).unwrap());
+}
+
+#[test]
+fn test_in_value_type_mismatch() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Value;
+    use matchingram::Error;
+
+    let cases = [
+        (
+            r#"(message.text in {1 2})"#,
+            Error::NotAString {
+                value: Value::Integer(1),
+            },
+        ),
+        (
+            r#"(message.text not_in {1})"#,
+            Error::NotAString {
+                value: Value::Integer(1),
+            },
+        ),
+        (
+            r#"(message.text in {"a" 1..5})"#,
+            Error::NotAString {
+                value: Value::Range(1, 5),
+            },
+        ),
+        (
+            r#"(message.from.id in {"5"})"#,
+            Error::NotAnInteger {
+                value: Value::from_str("5"),
+            },
+        ),
+        (
+            r#"(message.from.id in {5.0})"#,
+            Error::NotAnInteger {
+                value: Value::Decimal(5.0),
+            },
+        ),
+        (
+            r#"(message.chat.id not_in {1 "2"})"#,
+            Error::NotAnInteger {
+                value: Value::from_str("2"),
+            },
+        ),
+    ];
+
+    for (rule, err) in cases.iter() {
+        assert_eq!(Some(err), compile_rule(*rule).err().as_ref(), "{}", rule);
+    }
+}
+
+#[test]
+fn test_collect_matches() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let message_text = |text: &str| Message {
+        text: Some(String::from(text)),
+        ..Default::default()
+    };
+
+    let matcher = compile_rule(r#"(message.text any {"博彩" "菠菜" "赌场"})"#).unwrap();
+    assert_eq!(
+        vec![String::from("博彩"), String::from("菠菜")],
+        matcher
+            .collect_matches(&message_text("博彩菠菜一条龙"))
+            .unwrap()
+    );
+    assert!(matcher
+        .collect_matches(&message_text("你好"))
+        .unwrap()
+        .is_empty());
+
+    // 只收集获胜组中成立的条件，取反的条件不产生关键字。
+    let matcher = compile_rule(
+        r#"(message.text any {"招聘"} and not message.text any {"正规"}) or (message.text all {"承接" "广告"} and message.text contains "微信")"#,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            String::from("承接"),
+            String::from("广告"),
+            String::from("微信")
+        ],
+        matcher
+            .collect_matches(&message_text("正规招聘，承接广告加微信"))
+            .unwrap()
+    );
+    assert_eq!(
+        vec![String::from("招聘")],
+        matcher
+            .collect_matches(&message_text("高薪招聘，承接广告加微信"))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_from_username() {
+    let json = r#"{
+        "message_id": 1,
+        "from": {"id": 1, "is_bot": false, "first_name": "x", "username": "spammer2"}
+    }"#;
+    assert!(rule_match_json(
+        r#"(message.from.username in {"spammer1" "spammer2"})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(r#"(message.from.username eq "spammer2")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.username any {"spam" "scam"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.username hd "spam")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.username td "2")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.username in {"spammer1"})"#, json).unwrap());
+
+    // 用户名不存在时字段不存在，而不是出错。
+    let json = r#"{"message_id": 1, "from": {"id": 1, "is_bot": false, "first_name": "x"}}"#;
+    assert!(!rule_match_json(
+        r#"(message.from.username in {"spammer1" "spammer2"})"#,
+        json
+    )
+    .unwrap());
+    assert!(rule_match_json(r#"(not message.from.username eq "spammer2")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.username not_in {"spammer2"})"#, json).unwrap());
+}
+
+#[test]
+fn test_venue_string_operators() {
+    let json = r#"{
+        "message_id": 1,
+        "venue": {
+            "location": {"longitude": 104.9, "latitude": 11.5},
+            "title": "Golden Casino Hotel",
+            "address": "Sihanoukville, Cambodia"
+        }
+    }"#;
+
+    let cases = [
+        (
+            "eq",
+            r#""Golden Casino Hotel""#,
+            r#""Sihanoukville, Cambodia""#,
+        ),
+        ("any", r#"{"Casino" "博彩"}"#, r#"{"Cambodia" "柬埔寨"}"#),
+        (
+            "all",
+            r#"{"Golden" "Casino"}"#,
+            r#"{"Sihanoukville" "Cambodia"}"#,
+        ),
+        ("hd", r#""Golden""#, r#""Sihanoukville""#),
+        ("td", r#""Hotel""#, r#""Cambodia""#),
+        ("contains", r#""Casino""#, r#""Cambodia""#),
+    ];
+    for (operator, title, address) in &cases {
+        let rule = format!("(message.venue.title {} {})", operator, title);
+        assert!(rule_match_json(&rule, json).unwrap(), "{}", rule);
+        let rule = format!("(message.venue.address {} {})", operator, address);
+        assert!(rule_match_json(&rule, json).unwrap(), "{}", rule);
+        // 标题和地址互换后不成立。
+        let rule = format!("(message.venue.title {} {})", operator, address);
+        assert!(!rule_match_json(&rule, json).unwrap(), "{}", rule);
+        let rule = format!("(message.venue.address {} {})", operator, title);
+        assert!(!rule_match_json(&rule, json).unwrap(), "{}", rule);
+    }
+
+    // 没有地点时字段不存在。
+    let json = r#"{"message_id": 1, "text": "Golden Casino Hotel"}"#;
+    assert!(!rule_match_json(r#"(message.venue.title td "Hotel")"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.venue.address td "Cambodia")"#, json).unwrap());
+}
+
+#[test]
+fn test_from_last_name() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Field;
+
+    let matcher = compile_rule(r#"(message.from.last_name eq "Bot")"#).unwrap();
+    assert_eq!(Field::MessageFromLastName, matcher.groups[0][0].field);
+    assert_eq!(
+        "message.from.last_name",
+        Field::MessageFromLastName.to_string()
+    );
+
+    let json = r#"{
+        "message_id": 1,
+        "from": {"id": 1, "is_bot": false, "first_name": "Hentioe", "last_name": "Casino Bot"}
+    }"#;
+    assert!(rule_match_json(r#"(message.from.last_name eq "Casino Bot")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.last_name in {"Casino Bot" "Spam"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.last_name any {"Casino" "博彩"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.last_name all {"Casino" "Bot"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.last_name hd "Casino")"#, json).unwrap());
+    assert!(rule_match_json(r#"(message.from.last_name td "Bot")"#, json).unwrap());
+    // 不会匹配到名字上。
+    assert!(!rule_match_json(r#"(message.from.last_name eq "Hentioe")"#, json).unwrap());
+
+    // 没有姓时不成立，而不是出错。
+    let json = r#"{"message_id": 1, "from": {"id": 1, "is_bot": false, "first_name": "Hentioe"}}"#;
+    assert!(!rule_match_json(r#"(message.from.last_name eq "Casino Bot")"#, json).unwrap());
+    assert!(!rule_match_json(r#"(message.from.last_name any {"Casino"})"#, json).unwrap());
+    assert!(rule_match_json(r#"(not message.from.last_name td "Bot")"#, json).unwrap());
+}
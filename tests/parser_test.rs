@@ -1,5 +1,5 @@
 use matchingram::lexer::Lexer;
-use matchingram::models::{Location, Message};
+use matchingram::models::{Location, Message, User};
 use matchingram::parser::Parser;
 
 #[test]
@@ -11,7 +11,7 @@ fn test_parser() {
     lexer.tokenize().unwrap();
 
     let parser = Parser::new(&mut lexer).unwrap();
-    let mut matcher = parser.parse().unwrap();
+    let matcher = parser.parse().unwrap();
 
     let text1 = format!("Jay say: Hello!");
     let text2 = format!("小明说：你好！");
@@ -41,7 +41,7 @@ fn test_parse_number() {
     let input = rule.chars().collect::<Vec<_>>();
     let mut lexer = Lexer::new(&input);
     let parser = Parser::new(&mut lexer).unwrap();
-    let mut matcher = parser.parse().unwrap();
+    let matcher = parser.parse().unwrap();
 
     // TODO: 以下的 assertions 应该以测试 Matcher 结构的字段内容为主，而不是测试匹配结果。
 
@@ -60,3 +60,421 @@ fn test_parse_number() {
     assert!(matcher.match_message(&message1).unwrap());
     assert!(matcher.match_message(&message2).unwrap());
 }
+
+#[test]
+fn test_error_eq() {
+    use matchingram::{compile_rule, Error};
+
+    let r = compile_rule(r#"(message.text eq "a" "b")"#).map(|_| ());
+    assert_eq!(
+        Err(Error::ShouldCloseParenthesisHere {
+            line: 1,
+            column: 22
+        }),
+        r
+    );
+    assert_ne!(
+        Err(Error::ShouldCloseParenthesisHere {
+            line: 1,
+            column: 21
+        }),
+        r
+    );
+}
+
+#[test]
+fn test_error_line() {
+    use matchingram::{compile_rule, Error};
+
+    let rule = "(message.text eq \"a\"\n  and message.text.len gt 1\n  and message.text.len gt)";
+    let r = compile_rule(rule).map(|_| ());
+    assert_eq!(
+        Err(Error::ShouldValueHere {
+            line: 3,
+            column: 26
+        }),
+        r
+    );
+    assert_eq!(
+        "should be values from line 3, column 26",
+        r.unwrap_err().to_string()
+    );
+
+    let rule = "(message.text eq \"a\"\n  and message.text.len gt 1\n  and message.text.len gt 1_)";
+    let r = compile_rule(rule).map(|_| ());
+    assert_eq!(
+        Err(Error::InvalidDigitSeparator {
+            line: 3,
+            column: 28
+        }),
+        r
+    );
+}
+
+#[test]
+fn test_parse_digit_separators() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Value;
+
+    let matcher =
+        compile_rule(r#"(message.from.id eq 1_000_012 and message.from.id le 1,000,012)"#).unwrap();
+    assert_eq!(
+        Some(vec![Value::Integer(1000012)]),
+        matcher.groups[0][0].value
+    );
+    assert_eq!(
+        Some(vec![Value::Integer(1000012)]),
+        matcher.groups[0][1].value
+    );
+
+    let message = Message {
+        from: Some(matchingram::models::User {
+            id: 1000012,
+            is_bot: false,
+            first_name: String::from("小明"),
+            last_name: None,
+            username: None,
+            language_code: None,
+        }),
+        ..Default::default()
+    };
+    assert!(matchingram::rule_match(r#"(message.from.id eq 1_000_012)"#, &message).unwrap());
+}
+
+#[test]
+fn test_parse_comma_separated_values() {
+    use matchingram::compile_rule;
+
+    let expected = compile_rule(r#"(message.text any {"a" "b"} and message.message_id in {1 2})"#)
+        .unwrap()
+        .groups;
+    for rule in [
+        r#"(message.text any {"a", "b"} and message.message_id in {1, 2})"#,
+        r#"(message.text any {"a","b",} and message.message_id in {1,2})"#,
+        r#"(message.text any {"a" , "b"} and message.message_id in {1 2,})"#,
+    ] {
+        assert_eq!(expected, compile_rule(rule).unwrap().groups);
+    }
+}
+
+#[test]
+fn test_parse_with_aliases() {
+    use matchingram::matches::{Field, Matcher};
+    use matchingram::Error;
+    use std::collections::HashMap;
+
+    let mut aliases = HashMap::new();
+    aliases.insert(String::from("text"), Field::MessageText);
+    aliases.insert(String::from("text.len"), Field::MessageTextLen);
+    aliases.insert(String::from("caption.len"), Field::MessageCaptionLen);
+
+    let matcher = Matcher::from_rule_with_aliases(
+        r#"(text any {"x"} and text.len gt field:caption.len)"#,
+        aliases.clone(),
+    )
+    .unwrap();
+    assert_eq!(Field::MessageText, matcher.groups[0][0].field);
+
+    let message = Message {
+        text: Some(String::from("xyz")),
+        caption: Some(String::from("x")),
+        ..Default::default()
+    };
+    assert!(matcher.match_message(&message).unwrap());
+
+    // 完整的字段名称仍然可用。
+    assert!(
+        Matcher::from_rule_with_aliases(r#"(message.text any {"x"})"#, aliases.clone()).is_ok()
+    );
+    assert_eq!(
+        Err(Error::UnknownField {
+            field: String::from("txt")
+        }),
+        Matcher::from_rule_with_aliases(r#"(txt any {"x"})"#, aliases).map(|_| ())
+    );
+}
+
+#[test]
+fn test_parse_decimal() {
+    use matchingram::matches::{RefADecimal, Value};
+
+    let rule = r#"(message.location.latitude between {-0.2 0.2} and message.location.longitude gt 1920.1080)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    let parser = Parser::new(&mut lexer).unwrap();
+    let matcher = parser.parse().unwrap();
+
+    let conts = &matcher.groups[0];
+    assert_eq!(
+        Some(vec![Value::Decimal(-0.2), Value::Decimal(0.2)]),
+        conts[0].value
+    );
+    assert_eq!(Some(vec![Value::Decimal(1920.108)]), conts[1].value);
+    assert_eq!(
+        &1920.108,
+        conts[1].value.as_ref().unwrap().ref_a_decimal().unwrap()
+    );
+    assert!(Value::Integer(1).ref_a_decimal().is_err());
+}
+
+#[test]
+fn test_parse_integer() {
+    use matchingram::matches::{Field, Value};
+    use matchingram::operator::Operator;
+
+    let rule = r#"(message.text.len gt 120)"#;
+    let input = rule.chars().collect::<Vec<_>>();
+    let mut lexer = Lexer::new(&input);
+    let parser = Parser::new(&mut lexer).unwrap();
+    let matcher = parser.parse().unwrap();
+
+    let cont = &matcher.groups[0][0];
+    assert_eq!(Field::MessageTextLen, cont.field);
+    assert_eq!(Some(Operator::Gt), cont.operator);
+    assert_eq!(Some(vec![Value::Integer(120)]), cont.value);
+}
+
+#[test]
+fn test_error_pretty() {
+    use matchingram::{compile_rule, compile_rule_verbose};
+
+    let rule = r#"(message.text eq "a" "b")"#;
+    let pretty = compile_rule_verbose(rule).unwrap_err();
+    let lines = pretty.lines().collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            "error: it should be `)` (--> line 1, column 22)",
+            " --> 1:22",
+            "  |",
+            r#"1 | (message.text eq "a" "b")"#,
+            "  |                      ^",
+        ],
+        lines
+    );
+    let caret = lines[4].find('^').unwrap();
+    assert_eq!(r#""b""#, &lines[3][caret..caret + 3]);
+
+    let rule = "(message.text eq \"a\"\n  and message.text.len gt 1\n  and message.text.len gt 1_)";
+    let pretty = compile_rule(rule).unwrap_err().pretty(rule);
+    let lines = pretty.lines().collect::<Vec<_>>();
+    assert_eq!(" --> 3:28", lines[1]);
+    assert_eq!("3 |   and message.text.len gt 1_)", lines[3]);
+    let caret = lines[4].find('^').unwrap();
+    assert_eq!("_", &lines[3][caret..caret + 1]);
+
+    let rule = "(message.text eq \"a\"\n  and message.text.len gt)";
+    let pretty = compile_rule(rule).unwrap_err().pretty(rule);
+    let lines = pretty.lines().collect::<Vec<_>>();
+    let caret = lines[4].find('^').unwrap();
+    assert_eq!(")", &lines[3][caret..caret + 1]);
+
+    let rule = "(message.text xx \"a\")";
+    assert_eq!(
+        "error: unknown `\"xx\"` operator",
+        compile_rule_verbose(rule).unwrap_err()
+    );
+}
+
+#[test]
+fn test_parse_nested_groups() {
+    use matchingram::compile_rule;
+
+    let text_message = |text: &str, is_bot: bool| Message {
+        text: Some(text.to_owned()),
+        from: Some(User {
+            id: 1,
+            is_bot,
+            first_name: String::from("Bot"),
+            last_name: None,
+            username: None,
+            language_code: None,
+        }),
+        ..Default::default()
+    };
+
+    let rule =
+        r#"(message.text any {"博彩"} and (message.text any {"柬埔寨"} or message.from.is_bot))"#;
+    let matcher = compile_rule(rule).unwrap();
+    // 展开为 `(A and B) or (A and C)`。
+    assert_eq!(2, matcher.groups.len());
+    assert!(matcher
+        .match_message(&text_message("柬埔寨博彩", false))
+        .unwrap());
+    assert!(matcher
+        .match_message(&text_message("博彩招聘", true))
+        .unwrap());
+    assert!(!matcher
+        .match_message(&text_message("博彩招聘", false))
+        .unwrap());
+    assert!(!matcher
+        .match_message(&text_message("柬埔寨旅游", true))
+        .unwrap());
+
+    // 条件组前的 `not` 按德摩根定律分配：`A and not B and not C`。
+    let rule =
+        r#"(message.text any {"博彩"} and not (message.text any {"新闻"} or message.from.is_bot))"#;
+    let matcher = compile_rule(rule).unwrap();
+    assert_eq!(1, matcher.groups.len());
+    assert_eq!(3, matcher.groups[0].len());
+    assert!(matcher
+        .match_message(&text_message("博彩招聘", false))
+        .unwrap());
+    assert!(!matcher
+        .match_message(&text_message("博彩新闻", false))
+        .unwrap());
+    assert!(!matcher
+        .match_message(&text_message("博彩招聘", true))
+        .unwrap());
+
+    // 组内 `and` 的优先级高于 `or`。
+    let rule = r#"(message.text eq "a" and message.from.is_bot or message.text eq "b")"#;
+    let matcher = compile_rule(rule).unwrap();
+    assert!(matcher.match_message(&text_message("b", false)).unwrap());
+    assert!(!matcher.match_message(&text_message("a", false)).unwrap());
+    assert!(matcher.match_message(&text_message("a", true)).unwrap());
+
+    let mut nested = (1..=11)
+        .map(|i| format!("(message.text.len eq {} or message.text.len gt {})", i, i))
+        .collect::<Vec<_>>()
+        .join(" and ");
+    nested = format!("({})", nested);
+    assert_eq!(
+        "too many groups after expanding nested groups: 2048 (max 1024)",
+        compile_rule(nested).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_nested_groups_round_trip_to_flat() {
+    use matchingram::compile_rule;
+
+    let nested = compile_rule(
+        r#"((message.text any {"柬埔寨" "东南亚"} and message.text any {"菠菜" "博彩"}) or (message.text all {"承接" "广告"}))"#,
+    )
+    .unwrap();
+    let flat = compile_rule(
+        r#"(message.text any {"柬埔寨" "东南亚"} and message.text any {"菠菜" "博彩"}) or (message.text all {"承接" "广告"})"#,
+    )
+    .unwrap();
+
+    assert_eq!(flat.groups, nested.groups);
+}
+
+#[test]
+fn test_to_rule_round_trip() {
+    use matchingram::compile_rule;
+    use matchingram::models::Message;
+
+    let rules = [
+        r#"(message.text any {"柬埔寨" "东南亚"} and message.text any {"菠菜" "博彩"}) or (message.text all {"承接" "广告"})"#,
+        r#"(not message.from.is_bot and message.text.len gt 3)"#,
+        r#"(message.text eq "say \"hi\" \\ bye")"#,
+        r#"(message.text.max_amount ge 5000.0) or (message.text.max_amount between {0.5 1.25})"#,
+        r#"(message.text any {"博彩"} and (message.text any {"柬埔寨"} or message.from.is_bot))"#,
+    ];
+    let texts = [
+        "东南亚菠菜",
+        "承接广告",
+        "hello",
+        r#"say "hi" \ bye"#,
+        "earn 5000 USD daily",
+        "send 1 BTC",
+        "柬埔寨博彩",
+    ];
+
+    for rule in &rules {
+        let matcher = compile_rule(*rule).unwrap();
+        let restored = compile_rule(matcher.to_rule()).unwrap();
+
+        assert_eq!(matcher.groups, restored.groups);
+        assert_eq!(matcher.to_string(), restored.to_rule());
+        for text in &texts {
+            let message = Message {
+                text: Some(String::from(*text)),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                matcher.match_message(&message).unwrap(),
+                restored.match_message(&message).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_parse_dialect() {
+    use matchingram::{compile_rule, Error};
+
+    let v1 = "#lang v1\n(message.text any {\"柬埔寨\" \"东南亚\"}) or (message.from.id in {10086 10010})";
+    let v2 = "#lang v2\n(message.text any {\"柬埔寨\", \"东南亚\"}) or # 客服\n(message.from.id in {10086, 10010})";
+    assert_eq!(
+        compile_rule(v1).unwrap().groups,
+        compile_rule(v2).unwrap().groups
+    );
+
+    let r = compile_rule("#lang v1\n(message.from.id in {10086, 10010})");
+    assert!(matches!(
+        r,
+        Err(Error::InvalidDigitSeparator { .. }) | Err(Error::ParseFailed { .. })
+    ));
+}
+
+#[test]
+fn test_parse_escapes_by_dialect() {
+    use matchingram::compile_rule;
+    use matchingram::matches::Value;
+
+    let letter = |rule: &str| compile_rule(rule).unwrap().groups[0][0].value.clone();
+
+    // v2 还原转义的引号和反斜杠。
+    let rule = r#"(message.text eq "say \"hi\" C:\\")"#;
+    assert_eq!(Some(vec![Value::from_str(r#"say "hi" C:\"#)]), letter(rule));
+    assert_eq!(letter(rule), letter(&format!("#lang v2\n{}", rule)));
+
+    // v1 与加入转义前相同，反斜杠保持原样。
+    assert_eq!(
+        Some(vec![Value::from_str(r#"C:\\d"#)]),
+        letter("#lang v1\n(message.text eq \"C:\\\\d\")")
+    );
+    assert_eq!(
+        Some(vec![Value::from_str(r#"a\"#)]),
+        letter("#lang v1\n(message.text eq \"a\\\")")
+    );
+}
+
+#[test]
+fn test_parse_range() {
+    use matchingram::matches::Value;
+    use matchingram::{compile_rule, Error};
+
+    let matcher = compile_rule(r#"(message.from.id in {1..100 500 -10..-1})"#).unwrap();
+    assert_eq!(
+        Some(vec![
+            Value::Range(1, 100),
+            Value::Integer(500),
+            Value::Range(-10, -1)
+        ]),
+        matcher.groups[0][0].value
+    );
+    assert_eq!(
+        r#"(message.from.id in {1..100 500 -10..-1})"#,
+        matcher.to_rule()
+    );
+
+    // 范围只能用于 `in` 和 `not_in`，且起点不能大于终点。
+    assert_eq!(
+        Err(Error::InvalidValue {
+            value: String::from("100..1"),
+            field: String::from("message.from.id")
+        }),
+        compile_rule(r#"(message.from.id in {100..1})"#).map(|_| ())
+    );
+    assert_eq!(
+        Err(Error::InvalidValue {
+            value: String::from("1..100"),
+            field: String::from("message.from.id")
+        }),
+        compile_rule(r#"(message.from.id eq 1..100)"#).map(|_| ())
+    );
+}
@@ -0,0 +1,25 @@
+use matchingram::pattern::{check_pattern, MAX_PATTERN_LEN};
+
+#[test]
+fn test_check_pattern() {
+    assert!(check_pattern(r"casino|博彩").is_ok());
+    assert!(check_pattern(r"^(\d+)-(\d+)$").is_ok());
+    assert!(check_pattern(r"[(a+)+]").is_ok());
+
+    let pattern = "a".repeat(MAX_PATTERN_LEN + 1);
+    let r = check_pattern(&pattern);
+    assert!(r.is_err());
+    assert_eq!(
+        format!("the pattern `{}` is too complex: too long", pattern),
+        r.unwrap_err().to_string()
+    );
+
+    let r = check_pattern(r"^(a+)+$");
+    assert!(r.is_err());
+    assert_eq!(
+        "the pattern `^(a+)+$` is too complex: nested quantifiers",
+        r.unwrap_err().to_string()
+    );
+    assert!(check_pattern(r"^((ab)*c)*$").is_err());
+    assert!(check_pattern(r"^(a|b){2,}(x+)$").is_ok());
+}
@@ -0,0 +1,186 @@
+use matchingram::models::Message;
+use matchingram::ruleset::RuleSet;
+use matchingram::Error;
+
+#[test]
+fn test_from_lines() {
+    let input = r#"
+# 广告
+ad: (message.text all {"承接" "广告"})
+
+gambling: (message.text any {"菠菜" "博彩"})
+"#;
+    let rule_set = RuleSet::from_lines(input).unwrap();
+    assert_eq!(2, rule_set.rules.len());
+    assert_eq!("ad", rule_set.rules[0].label);
+
+    let message = Message {
+        text: Some(String::from("承接博彩广告业务")),
+        ..Default::default()
+    };
+    assert_eq!(Some("ad"), rule_set.match_message(&message).unwrap());
+    let message = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+    assert_eq!(None, rule_set.match_message(&message).unwrap());
+
+    let input = r#"
+ad => (message.text all {"承接" "广告"})
+gambling => (message.text any {"菠菜" "博彩"})
+"#;
+    let rule_set = RuleSet::from_lines_with_delimiter(input, "=>").unwrap();
+    assert_eq!("gambling", rule_set.rules[1].label);
+}
+
+#[test]
+fn test_from_lines_error() {
+    let input = r#"# 规则
+ad: (message.text all {"承接" "广告"})
+gambling: (message.text any {"菠菜" "博彩"}
+"#;
+    let r = RuleSet::from_lines(input);
+    assert!(r.is_err());
+    let err = r.unwrap_err();
+    if let Error::RuleSetLoadFailed { errors } = &err {
+        assert_eq!(1, errors.len());
+        assert_eq!(3, errors[0].line);
+    } else {
+        panic!("unexpected error: {}", err);
+    }
+
+    let r = RuleSet::from_lines("(message.text eq \"hello\")");
+    assert!(r.is_err());
+    assert_eq!(
+        "failed to load rule set: line 1: missing delimiter `:` between label and rule",
+        r.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_policy() {
+    use matchingram::models::User;
+    use matchingram::ruleset::{Decision, Policy};
+
+    let policy = Policy {
+        allow: RuleSet::from_lines(
+            r#"
+admin: (message.from.id eq 10086)
+bot: (message.from.is_bot)
+"#,
+        )
+        .unwrap(),
+        deny: RuleSet::from_lines(r#"gambling: (message.text any {"菠菜" "博彩"})"#).unwrap(),
+    };
+    let user = |id| User {
+        id,
+        is_bot: false,
+        first_name: String::from("user"),
+        last_name: None,
+        username: None,
+        language_code: None,
+    };
+
+    let message = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        from: Some(user(10086)),
+        ..Default::default()
+    };
+    assert_eq!(
+        Decision::Allow {
+            label: "admin",
+            vetoed: "gambling"
+        },
+        policy.decide(&message).unwrap()
+    );
+
+    let message = Message {
+        text: Some(String::from("东南亚博彩招聘")),
+        from: Some(user(10010)),
+        ..Default::default()
+    };
+    assert_eq!(
+        Decision::Deny { label: "gambling" },
+        policy.decide(&message).unwrap()
+    );
+
+    let message = Message {
+        text: Some(String::from("你好")),
+        from: Some(user(10086)),
+        ..Default::default()
+    };
+    assert_eq!(Decision::Pass, policy.decide(&message).unwrap());
+}
+
+#[test]
+fn test_policy_by_priority() {
+    use matchingram::ruleset::{Decision, Policy};
+
+    let policy = Policy {
+        allow: RuleSet::from_lines(
+            r#"
+short: (message.text.len lt 20)
+trusted@5: (message.text contains "官方")
+"#,
+        )
+        .unwrap(),
+        deny: RuleSet::from_lines(
+            r#"
+ad: (message.text all {"承接" "广告"})
+gambling@10: (message.text any {"菠菜" "博彩"})
+"#,
+        )
+        .unwrap(),
+    };
+
+    let message = Message {
+        text: Some(String::from("官方承接博彩广告")),
+        ..Default::default()
+    };
+    assert_eq!(
+        Decision::Allow {
+            label: "trusted",
+            vetoed: "gambling"
+        },
+        policy.decide(&message).unwrap()
+    );
+}
+
+#[test]
+fn test_match_by_priority() {
+    let input = r#"
+ad: (message.text all {"承接" "广告"})
+gambling@10: (message.text any {"菠菜" "博彩"})
+spam@-1: (message.text.len gt 2)
+business@10: (message.text contains "承接")
+"#;
+    let rule_set = RuleSet::from_lines(input).unwrap();
+    assert_eq!(10, rule_set.rules[1].priority);
+    assert_eq!(-1, rule_set.rules[2].priority);
+
+    let message = Message {
+        text: Some(String::from("承接博彩广告业务")),
+        ..Default::default()
+    };
+    assert_eq!(
+        vec!["gambling", "business", "ad", "spam"],
+        rule_set.match_all(&message).unwrap()
+    );
+    let rule = rule_set.match_highest_priority(&message).unwrap().unwrap();
+    assert_eq!("gambling", rule.label);
+    assert_eq!(Some("gambling"), rule_set.match_message(&message).unwrap());
+
+    let message = Message {
+        text: Some(String::from("你好")),
+        ..Default::default()
+    };
+    assert!(rule_set.match_all(&message).unwrap().is_empty());
+    assert!(rule_set.match_highest_priority(&message).unwrap().is_none());
+    assert_eq!(None, rule_set.match_message(&message).unwrap());
+
+    let r = RuleSet::from_lines("ad@high: (message.text eq \"hello\")");
+    assert_eq!(
+        "failed to load rule set: line 1: invalid rule priority `high`",
+        r.unwrap_err().to_string()
+    );
+}
@@ -0,0 +1,69 @@
+use matchingram::text::{
+    amounts, decode_base64, decode_percent, decode_segments, is_blank, max_amount,
+    normalize_line_endings, strip_emoji_modifiers, upper_ratio,
+};
+
+#[test]
+fn test_decode() {
+    assert_eq!(Some(b"casino".to_vec()), decode_base64("Y2FzaW5v"));
+    assert_eq!(Some(b"hello".to_vec()), decode_base64("aGVsbG8="));
+    assert_eq!(Some(b"hello".to_vec()), decode_base64("aGVsbG8"));
+    assert_eq!(None, decode_base64("aGVsb!8="));
+
+    assert_eq!(Some(String::from("a b")), decode_percent("a%20b"));
+    assert_eq!(None, decode_percent("a b"));
+    assert_eq!(None, decode_percent("a%2"));
+
+    assert_eq!(
+        vec![String::from("casino")],
+        decode_segments("hi Y2FzaW5v there")
+    );
+    assert!(decode_segments("password").is_empty());
+}
+
+#[test]
+fn test_normalize_line_endings() {
+    assert_eq!("a\nb\nc\n", normalize_line_endings("a\r\nb\rc\n"));
+    assert_eq!("\n\n", normalize_line_endings("\r\r\n"));
+}
+
+#[test]
+fn test_strip_emoji_modifiers() {
+    assert_eq!("👍", strip_emoji_modifiers("👍🏽"));
+    assert_eq!("❤", strip_emoji_modifiers("❤\u{FE0F}"));
+    assert_eq!("👍👎", strip_emoji_modifiers("👍🏻👎🏿"));
+    assert_eq!("🎲", strip_emoji_modifiers("🎲"));
+}
+
+#[test]
+fn test_upper_ratio() {
+    assert_eq!(100, upper_ratio("SPAM!!!"));
+    assert_eq!(50, upper_ratio("AbCd"));
+    assert_eq!(33, upper_ratio("Abc"));
+    assert_eq!(0, upper_ratio("广告 123"));
+    assert_eq!(0, upper_ratio(""));
+}
+
+#[test]
+fn test_amounts() {
+    assert_eq!(vec![5000.0], amounts("earn 5000 USD daily"));
+    assert_eq!(vec![0.5], amounts("send 0.5 BTC now"));
+    assert_eq!(vec![1200.5, 30.0], amounts("$1,200.50 or 30€"));
+    assert_eq!(vec![100.0, 8.0], amounts("日赚100元，只需8块"));
+    assert_eq!(vec![20.0], amounts("20usdt"));
+    assert!(amounts("5 usdollar").is_empty());
+    assert!(amounts("call 10086 now").is_empty());
+    assert!(amounts("").is_empty());
+
+    assert_eq!(Some(1200.5), max_amount("$1,200.50 or 30€"));
+    assert_eq!(None, max_amount("no money here"));
+}
+
+#[test]
+fn test_is_blank() {
+    assert!(is_blank(""));
+    assert!(is_blank(" \t\r\n\u{3000}"));
+    assert!(is_blank("\u{200B}\u{2800}\u{202E}"));
+    assert!(!is_blank("\u{200B}a"));
+    assert!(!is_blank("。"));
+}